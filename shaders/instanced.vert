@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec4 position;
+layout(location = 1) in vec2 uv;
+
+layout(location = 2) in mat4 instance_matrix;
+
+layout(location = 0) out vec2 tex_coords;
+
+void main()
+{
+    gl_Position = instance_matrix * position;
+
+    tex_coords = uv;
+}
@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+
+layout(location = 0) out vec4 f_color;
+
+layout(input_attachment_index = 0, set = 0, binding = 0) uniform subpassInput scene;
+
+void main()
+{
+    f_color = subpassLoad(scene);
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+
+layout(location = 0) out vec4 f_color;
+
+layout(push_constant) uniform SdfConstants
+{
+    vec4 color;
+    vec2 size;
+    float radius;
+    float softness;
+} constants;
+
+void main()
+{
+    vec2 half_size = constants.size * 0.5;
+    vec2 position = (tex_coords - 0.5) * constants.size;
+
+    float radius = min(constants.radius, min(half_size.x, half_size.y));
+
+    vec2 corner = abs(position) - half_size + radius;
+    float distance = length(max(corner, 0.0)) + min(max(corner.x, corner.y), 0.0) - radius;
+
+    float softness = max(constants.softness, fwidth(distance));
+    float alpha = 1.0 - smoothstep(-softness, 0.0, distance);
+
+    f_color = vec4(constants.color.rgb, constants.color.a * alpha);
+}
\ No newline at end of file
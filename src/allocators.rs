@@ -14,43 +14,35 @@ use vulkano::{
     }
 };
 
+pub use crate::object::object_allocator::{ObjectAllocator, FrameSubbuffers, Recycler};
 
-#[derive(Debug, Clone, Copy)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct UniformLocation
 {
     pub set: u32,
     pub binding: u32
 }
 
-type ThisMemoryAllocator = GenericMemoryAllocator<FreeListAllocator>;
+pub(crate) type ThisMemoryAllocator = GenericMemoryAllocator<FreeListAllocator>;
 
-#[derive(Debug)]
-pub struct ObjectAllocator
-{
-	allocator: SubbufferAllocator
-}
+pub const DEFAULT_MEMORY_FILTER: MemoryTypeFilter = MemoryTypeFilter::PREFER_DEVICE
+    .union(MemoryTypeFilter::HOST_SEQUENTIAL_WRITE);
 
-impl ObjectAllocator
+pub(crate) fn subbuffer_allocator(
+    allocator: Arc<ThisMemoryAllocator>,
+    buffer_usage: BufferUsage,
+    memory_type_filter: MemoryTypeFilter
+) -> SubbufferAllocator
 {
-	pub fn new(allocator: Arc<ThisMemoryAllocator>) -> Self
-	{
-		let allocator = SubbufferAllocator::new(
-			allocator,
-			SubbufferAllocatorCreateInfo{
-				buffer_usage: BufferUsage::VERTEX_BUFFER | BufferUsage::TRANSFER_DST,
-                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
-                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
-				..Default::default()
-			}
-		);
-
-		Self{allocator}
-	}
-
-	pub fn subbuffer<T: BufferContents>(&self, size: u64) -> Subbuffer<[T]>
-	{
-		self.allocator.allocate_slice(size).unwrap()
-	}
+	SubbufferAllocator::new(
+		allocator,
+		SubbufferAllocatorCreateInfo{
+			buffer_usage,
+			memory_type_filter,
+			..Default::default()
+		}
+	)
 }
 
 #[derive(Debug)]
@@ -63,17 +55,16 @@ impl UniformAllocator
 {
 	pub fn new(allocator: Arc<ThisMemoryAllocator>) -> Self
 	{
-		let allocator = SubbufferAllocator::new(
-			allocator,
-			SubbufferAllocatorCreateInfo{
-				buffer_usage: BufferUsage::UNIFORM_BUFFER,
-                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
-                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
-				..Default::default()
-			}
-		);
+		Self::new_with(allocator, BufferUsage::UNIFORM_BUFFER, DEFAULT_MEMORY_FILTER)
+	}
 
-		Self{allocator}
+	pub fn new_with(
+		allocator: Arc<ThisMemoryAllocator>,
+		buffer_usage: BufferUsage,
+		memory_type_filter: MemoryTypeFilter
+	) -> Self
+	{
+		Self{allocator: subbuffer_allocator(allocator, buffer_usage, memory_type_filter)}
 	}
 
 	pub fn allocate_sized<T: BufferContents>(&self) -> Subbuffer<T>
@@ -3,7 +3,8 @@ use std::{
     fmt,
     collections::HashMap,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, mpsc::{self, Receiver}},
+    thread,
     ops::{Index, IndexMut}
 };
 
@@ -20,7 +21,7 @@ use crate::{
     object::{
         resource_uploader::ResourceUploader,
         model::Model,
-        texture::{SimpleImage, RgbaImage, Texture}
+        texture::{SimpleImage, RgbaImage, Texture, Color}
     }
 };
 
@@ -106,6 +107,59 @@ impl FilesLoader
 	}
 }
 
+pub struct BackgroundLoader<Id, T>
+{
+    receiver: Receiver<(Id, Option<T>)>,
+    remaining: usize
+}
+
+impl<Id, T> BackgroundLoader<Id, T>
+where
+    Id: Send + 'static,
+    T: Send + 'static
+{
+    pub fn new<F>(items: Vec<(Id, PathBuf)>, f: F) -> Self
+    where
+        F: Fn(&Path) -> Option<T> + Send + 'static
+    {
+        let remaining = items.len();
+
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move ||
+        {
+            for (id, path) in items
+            {
+                // the receiver is gone, nobody cares about the rest
+                if sender.send((id, f(&path))).is_err()
+                {
+                    return;
+                }
+            }
+        });
+
+        Self{receiver, remaining}
+    }
+
+    pub fn try_iter(&mut self) -> impl Iterator<Item=(Id, Option<T>)> + '_
+    {
+        self.receiver.try_iter().inspect(|_|
+        {
+            self.remaining -= 1;
+        })
+    }
+
+    pub fn remaining(&self) -> usize
+    {
+        self.remaining
+    }
+
+    pub fn is_done(&self) -> bool
+    {
+        self.remaining == 0
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TextureId(usize);
 
@@ -194,7 +248,7 @@ impl<I, T> IdsStorage<I, T>
     {
         let id: I = self.data.len().into();
 
-        self.ids.insert(item.0.replace('\\', "/"), id.clone());
+        self.ids.insert(Self::normalized_name(&item.0), id.clone());
         self.data.push(item.1);
 
         id
@@ -211,9 +265,22 @@ impl<I, T> IdsStorage<I, T>
         id
     }
 
+    pub fn try_get_id(&self, name: &str) -> Option<I>
+    where
+        I: Clone
+    {
+        self.ids.get(&Self::normalized_name(name)).cloned()
+    }
+
     pub fn get_id(&self, name: &str) -> &I
     {
-        self.ids.get(name).unwrap_or_else(|| panic!("asset named `{name}` doesnt exist"))
+        self.ids.get(&Self::normalized_name(name)).unwrap_or_else(|| panic!("asset named `{name}` doesnt exist"))
+    }
+
+    // names r paths relative to the assets folder, they always use forward slashes
+    fn normalized_name(name: &str) -> String
+    {
+        name.replace('\\', "/")
     }
 
     pub fn keys(&self) -> impl Iterator<Item=&String>
@@ -221,6 +288,13 @@ impl<I, T> IdsStorage<I, T>
         self.ids.keys()
     }
 
+    pub fn named_ids(&self) -> impl Iterator<Item=(&str, I)> + '_
+    where
+        I: Clone
+    {
+        self.ids.iter().map(|(name, id)| (name.as_str(), id.clone()))
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T>
     {
         self.data.iter_mut()
@@ -251,11 +325,19 @@ where
 
 pub type ShadersQuery = Box<dyn Fn(&Path) -> ShaderId>;
 
+type ModelsStorage = IdsStorage<ModelId, Arc<RwLock<Model>>>;
+type TexturesStorage = IdsStorage<TextureId, Arc<RwLock<Texture>>>;
+
+type TexturesLoader = BackgroundLoader<(TextureId, ShaderId), RgbaImage>;
+
 pub struct Assets
 {
     textures_path: Option<PathBuf>,
-    textures: IdsStorage<TextureId, Arc<RwLock<Texture>>>,
-	models: IdsStorage<ModelId, Arc<RwLock<Model>>>,
+    models_path: Option<PathBuf>,
+    textures: TexturesStorage,
+	models: ModelsStorage,
+    textures_loader: Option<TexturesLoader>,
+    models_loader: Option<BackgroundLoader<ModelId, Model>>,
     texture_location: UniformLocation,
     shaders_query: ShadersQuery
 }
@@ -266,6 +348,8 @@ impl Assets
         resource_uploader: &mut ResourceUploader,
         textures_path: Option<TexturesPath>,
         models_path: Option<ModelsPath>,
+        background_textures: bool,
+        background_models: bool,
         texture_location: UniformLocation,
         shaders_query: ShadersQuery
     ) -> Self
@@ -274,33 +358,50 @@ impl Assets
         ModelsPath: AsRef<Path>
     {
         let output_textures_path = textures_path.as_ref().map(|x| x.as_ref().to_owned());
-        let textures = Self::load_resource(textures_path, |path|
+        let output_models_path = models_path.as_ref().map(|x| x.as_ref().to_owned());
+        let (textures, textures_loader) = if background_textures
         {
-            FilesLoader::load_images(path).map(|named_value|
+            Self::load_textures_background(
+                resource_uploader,
+                textures_path,
+                texture_location,
+                &shaders_query
+            )
+        } else
+        {
+            let textures = Self::load_resource(textures_path, |path|
             {
-                let shader = shaders_query(&PathBuf::from(&named_value.name));
+                Self::load_textures(resource_uploader, path, texture_location, &shaders_query)
+            });
 
-                named_value.map(|image|
-                {
-                    Texture::new(resource_uploader, image, texture_location, shader)
-                })
-            })
-        });
+            (textures, None)
+        };
 
-        let mut models = Self::load_resource(models_path, |path|
+        let (mut models, models_loader) = if background_models
+        {
+            Self::load_models_background(models_path)
+        } else
         {
-            FilesLoader::load(path).map(|named_value|
+            let models = Self::load_resource(models_path, |path|
             {
-                named_value.map(|path| Model::load(path).unwrap())
-            })
-        });
+                FilesLoader::load(path).map(|named_value|
+                {
+                    named_value.map(|path| Model::load(path).unwrap())
+                })
+            });
+
+            (models, None)
+        };
 
         models.extend(Self::create_default_models());
 
         Self{
             textures_path: output_textures_path,
+            models_path: output_models_path,
             textures,
             models,
+            textures_loader,
+            models_loader,
             texture_location,
             shaders_query
         }
@@ -322,6 +423,207 @@ impl Assets
         }).unwrap_or_default()
     }
 
+    fn load_textures(
+        resource_uploader: &mut ResourceUploader,
+        path: impl AsRef<Path>,
+        location: UniformLocation,
+        shaders_query: &ShadersQuery
+    ) -> impl Iterator<Item=NamedValue<Texture>>
+    {
+        let (names, images): (Vec<_>, Vec<_>) = FilesLoader::load_images(path).map(|NamedValue{name, value}|
+        {
+            let shader = shaders_query(&PathBuf::from(&name));
+
+            (name, (value, shader))
+        }).unzip();
+
+        let textures = Texture::new_many(resource_uploader, images, location);
+
+        names.into_iter().zip(textures).map(|(name, value)| NamedValue{name, value})
+    }
+
+    // images get decoded on another thread, only the upload happens in poll_loading
+    fn load_textures_background<P: AsRef<Path>>(
+        resource_uploader: &mut ResourceUploader,
+        maybe_path: Option<P>,
+        location: UniformLocation,
+        shaders_query: &ShadersQuery
+    ) -> (TexturesStorage, Option<TexturesLoader>)
+    {
+        let path = if let Some(path) = maybe_path
+        {
+            path
+        } else
+        {
+            return (IdsStorage::default(), None);
+        };
+
+        let mut textures = IdsStorage::default();
+
+        // every texture is transparent until its loaded, objects see the real one after
+        let items = FilesLoader::load(path).map(|NamedValue{name, value}|
+        {
+            let shader = shaders_query(&PathBuf::from(&name));
+
+            let placeholder = Texture::solid(resource_uploader, Color::new(0, 0, 0, 0), location, shader);
+            let id = textures.insert((name, Arc::new(RwLock::new(placeholder))));
+
+            ((id, shader), value)
+        }).collect();
+
+        let loader = BackgroundLoader::new(items, |path|
+        {
+            RgbaImage::load(path).map_err(|err|
+            {
+                eprintln!("error loading texture at {}: {err}", path.display());
+            }).ok()
+        });
+
+        (textures, Some(loader))
+    }
+
+    fn load_models_background<P: AsRef<Path>>(
+        maybe_path: Option<P>
+    ) -> (ModelsStorage, Option<BackgroundLoader<ModelId, Model>>)
+    {
+        let path = if let Some(path) = maybe_path
+        {
+            path
+        } else
+        {
+            return (IdsStorage::default(), None);
+        };
+
+        let mut models = IdsStorage::default();
+
+        // every model is a square until its actually parsed
+        let items = FilesLoader::load(path).map(|NamedValue{name, value}|
+        {
+            let id = models.insert((name, Arc::new(RwLock::new(Model::square(1.0)))));
+
+            (id, value)
+        }).collect();
+
+        let loader = BackgroundLoader::new(items, |path|
+        {
+            Model::load(path).map_err(|err|
+            {
+                eprintln!("error parsing model at {}: {err:?}", path.display());
+            }).ok()
+        });
+
+        (models, Some(loader))
+    }
+
+    pub fn poll_loading(&mut self, resource_uploader: &mut ResourceUploader)
+    {
+        if let Some(loader) = self.textures_loader.as_mut()
+        {
+            let (ids, images): (Vec<_>, Vec<_>) = loader.try_iter().filter_map(|((id, shader), image)|
+            {
+                image.map(|image| (id, (image, shader)))
+            }).unzip();
+
+            let textures = Texture::new_many(resource_uploader, images, self.texture_location);
+
+            ids.into_iter().zip(textures).for_each(|(id, texture)|
+            {
+                *self.textures[id].write() = texture;
+            });
+
+            if loader.is_done()
+            {
+                self.textures_loader = None;
+            }
+        }
+
+        if let Some(loader) = self.models_loader.as_mut()
+        {
+            loader.try_iter().for_each(|(id, model)|
+            {
+                if let Some(model) = model
+                {
+                    // objects created with the fallback pick it up, they resize their buffers
+                    *self.models[id].write() = model;
+                }
+            });
+
+            if loader.is_done()
+            {
+                self.models_loader = None;
+            }
+        }
+    }
+
+    // updates the assets behind the existing ids so live objects see the new data
+    pub fn reload(&mut self, resource_uploader: &mut ResourceUploader)
+    {
+        self.reload_textures(resource_uploader);
+
+        if let Some(models_path) = self.models_path.as_ref()
+        {
+            FilesLoader::load(models_path).for_each(|NamedValue{name, value: path}|
+            {
+                let model = match Model::load(&path)
+                {
+                    Ok(x) => x,
+                    Err(err) =>
+                    {
+                        eprintln!("error reloading model at {}: {err:?}", path.display());
+                        return;
+                    }
+                };
+
+                match self.models.try_get_id(&name)
+                {
+                    Some(id) => *self.models[id].write() = model,
+                    None => { self.models.insert((name, Arc::new(RwLock::new(model)))); }
+                }
+            });
+        }
+    }
+
+    pub fn reload_textures(&mut self, resource_uploader: &mut ResourceUploader)
+    {
+        if let Some(textures_path) = self.textures_path.as_ref()
+        {
+            let textures = Self::load_textures(
+                resource_uploader,
+                textures_path,
+                self.texture_location,
+                &self.shaders_query
+            );
+
+            textures.for_each(|NamedValue{name, value: texture}|
+            {
+                match self.textures.try_get_id(&name)
+                {
+                    Some(id) => *self.textures[id].write() = texture,
+                    None => { self.textures.insert((name, Arc::new(RwLock::new(texture)))); }
+                }
+            });
+        }
+    }
+
+    pub fn models_loaded(&self) -> bool
+    {
+        self.models_loader.is_none()
+    }
+
+    pub fn textures_loaded(&self) -> bool
+    {
+        self.textures_loader.is_none()
+    }
+
+    // how many textures and models r still being loaded in the background
+    pub fn loading_remaining(&self) -> usize
+    {
+        let textures = self.textures_loader.as_ref().map(|x| x.remaining()).unwrap_or(0);
+        let models = self.models_loader.as_ref().map(|x| x.remaining()).unwrap_or(0);
+
+        textures + models
+    }
+
     pub fn default_model(&self, id: DefaultModel) -> ModelId
     {
         self.model_id(id.into())
@@ -342,6 +644,16 @@ impl Assets
         &self.textures[id]
     }
 
+    pub fn texture_names(&self) -> impl Iterator<Item=(&str, TextureId)> + '_
+    {
+        self.textures.named_ids()
+    }
+
+    pub fn model_names(&self) -> impl Iterator<Item=(&str, ModelId)> + '_
+    {
+        self.models.named_ids()
+    }
+
     pub fn model_id(&self, name: &str) -> ModelId
     {
         *self.models.get_id(name)
@@ -381,6 +693,35 @@ impl Assets
         self.textures.insert((name.to_owned(), Arc::new(RwLock::new(texture))))
     }
 
+    // rereads just this texture from the textures folder, objects using it see the new one
+    pub fn reload_texture(
+        &mut self,
+        builder_wrapper: &mut BuilderWrapper,
+        name: &str
+    ) -> Option<TextureId>
+    {
+        let id = self.textures.try_get_id(name)?;
+        let filepath = self.textures_path.as_ref()?.join(name);
+
+        let image = match RgbaImage::load(&filepath)
+        {
+            Ok(x) => x,
+            Err(err) =>
+            {
+                eprintln!("error reloading texture at {}: {err}", filepath.display());
+                return None;
+            }
+        };
+
+        let shader = (self.shaders_query)(&PathBuf::from(name));
+
+        let texture = builder_wrapper.create_texture(image, self.texture_location, shader);
+
+        *self.textures[id].write() = texture;
+
+        Some(id)
+    }
+
     pub fn add_textures<T>(&mut self, textures: T)
     where
         T: IntoIterator<Item=(String, Texture)>
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::{
+    UniformLocation,
+    ShaderId,
+    object::{
+        resource_uploader::ResourceUploader,
+        texture::{Texture, Color, SimpleImage}
+    }
+};
+
+
+// uvs of the top left and bottom right corners inside of the atlas texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect
+{
+    pub min: [f32; 2],
+    pub max: [f32; 2]
+}
+
+impl UvRect
+{
+    pub fn full() -> Self
+    {
+        Self{min: [0.0, 0.0], max: [1.0, 1.0]}
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackedAtlas
+{
+    pub image: SimpleImage,
+    pub rects: HashMap<String, UvRect>
+}
+
+#[derive(Debug)]
+pub struct Atlas
+{
+    images: Vec<(String, SimpleImage)>,
+    padding: usize
+}
+
+impl Default for Atlas
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Atlas
+{
+    pub fn new() -> Self
+    {
+        Self{images: Vec::new(), padding: 1}
+    }
+
+    // empty pixels between the images so filtering doesnt bleed into the neighbors
+    pub fn with_padding(mut self, padding: usize) -> Self
+    {
+        self.padding = padding;
+
+        self
+    }
+
+    pub fn push(&mut self, name: impl Into<String>, image: SimpleImage)
+    {
+        self.images.push((name.into(), image));
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.images.is_empty()
+    }
+
+    // packs the images into shelves, tallest first
+    pub fn pack(mut self) -> PackedAtlas
+    {
+        let padding = self.padding;
+
+        self.images.sort_by(|(_, a), (_, b)| b.height.cmp(&a.height).then(b.width.cmp(&a.width)));
+
+        let area: usize = self.images.iter().map(|(_, image)|
+        {
+            (image.width + padding) * (image.height + padding)
+        }).sum();
+
+        let widest = self.images.iter().map(|(_, image)| image.width + padding).max().unwrap_or(1);
+
+        let width = widest.max((area as f32).sqrt().ceil() as usize).next_power_of_two();
+
+        let mut positions = Vec::with_capacity(self.images.len());
+
+        let (mut x, mut y) = (0, 0);
+        let mut shelf_height = 0;
+
+        self.images.iter().for_each(|(_, image)|
+        {
+            if x + image.width > width
+            {
+                x = 0;
+                y += shelf_height + padding;
+                shelf_height = 0;
+            }
+
+            positions.push((x, y));
+
+            x += image.width + padding;
+            shelf_height = shelf_height.max(image.height);
+        });
+
+        let height = (y + shelf_height).max(1).next_power_of_two();
+
+        let mut atlas = SimpleImage::new(vec![Color::new(0, 0, 0, 0); width * height], width, height);
+
+        let rects = self.images.into_iter().zip(positions).map(|((name, image), (x, y))|
+        {
+            atlas.blit(&image, x, y);
+
+            let rect = UvRect{
+                min: [x as f32 / width as f32, y as f32 / height as f32],
+                max: [
+                    (x + image.width) as f32 / width as f32,
+                    (y + image.height) as f32 / height as f32
+                ]
+            };
+
+            (name, rect)
+        }).collect();
+
+        PackedAtlas{image: atlas, rects}
+    }
+
+    pub fn build(
+        self,
+        resource_uploader: &mut ResourceUploader,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> (Texture, HashMap<String, UvRect>)
+    {
+        let PackedAtlas{image, rects} = self.pack();
+
+        (Texture::new(resource_uploader, image.into(), location, shader), rects)
+    }
+}
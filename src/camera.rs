@@ -7,21 +7,36 @@ use nalgebra::{
     Point3,
     Vector2,
     Vector3,
+    Vector4,
+    Rotation2,
     geometry::Orthographic3,
     Matrix4
 };
 
+use vulkano::buffer::BufferContents;
+
 use camera_transform::CameraTransform;
 
+pub use camera_transform::CameraTransformConfig;
+
 mod camera_transform;
 
 
+#[derive(BufferContents, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct CameraMatrices
+{
+    pub projection_view: [[f32; 4]; 4],
+    pub projection_view_inverse: [[f32; 4]; 4]
+}
+
 #[derive(Debug, Clone)]
 pub struct Camera
 {
     projection: Matrix4<f32>,
     view: CameraTransform,
     projection_view: Matrix4<f32>,
+    projection_view_inverse: Matrix4<f32>,
     aspect: f32,
     scale: f32,
     size: Vector2<f32>,
@@ -31,18 +46,29 @@ pub struct Camera
 impl Camera
 {
     pub fn new(aspect: f32, z_planes: Range<f32>) -> Self
+    {
+        Self::new_with_config(aspect, z_planes, CameraTransformConfig::default())
+    }
+
+    pub fn new_with_config(
+        aspect: f32,
+        z_planes: Range<f32>,
+        config: CameraTransformConfig
+    ) -> Self
     {
         let size = Self::aspect_size(aspect);
         let projection = Self::create_projection(size, &z_planes);
 
-        let view = CameraTransform::new(Default::default());
+        let view = CameraTransform::new(config);
 
         let projection_view = Self::create_projection_view(projection, view.matrix());
+        let projection_view_inverse = Self::inverse(projection_view);
 
         Self{
             projection,
             view,
             projection_view,
+            projection_view_inverse,
             aspect,
             scale: 1.0,
             size,
@@ -94,6 +120,7 @@ impl Camera
     pub fn screen_position(&self, position: Vector2<f32>) -> Vector2<f32>
     {
         let offset = position - self.position().coords.xy();
+        let offset = Rotation2::new(-self.rotation()) * offset;
 
         offset.component_div(&self.size())
     }
@@ -108,11 +135,33 @@ impl Camera
         self.view.position()
     }
 
+    pub fn forward(&self) -> &Vector3<f32>
+    {
+        self.view.forward()
+    }
+
+    pub fn up(&self) -> &Vector3<f32>
+    {
+        self.view.up()
+    }
+
     pub fn set_position(&mut self, position: Point3<f32>)
     {
         self.view.set_position(position);
     }
 
+    pub fn rotation(&self) -> f32
+    {
+        self.view.rotation()
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32)
+    {
+        self.view.set_rotation(rotation);
+
+        self.update();
+    }
+
     pub fn set_position_x(&mut self, position: f32)
     {
         self.view.set_position_x(position);
@@ -142,6 +191,13 @@ impl Camera
     {
         self.projection_view =
             Self::create_projection_view(self.projection, self.view.matrix());
+
+        self.projection_view_inverse = Self::inverse(self.projection_view);
+    }
+
+    fn inverse(matrix: Matrix4<f32>) -> Matrix4<f32>
+    {
+        matrix.try_inverse().unwrap_or_else(Matrix4::identity)
     }
 
     pub fn create_projection_view(projection: Matrix4<f32>, view: Matrix4<f32>) -> Matrix4<f32>
@@ -154,6 +210,42 @@ impl Camera
         self.projection_view
     }
 
+    pub fn projection_view_inverse(&self) -> Matrix4<f32>
+    {
+        self.projection_view_inverse
+    }
+
+    // normalized is in 0..1 screen space, same as the mouse position
+    pub fn screen_to_ray(&self, normalized: Vector2<f32>) -> (Vector3<f32>, Vector3<f32>)
+    {
+        let ndc = normalized * 2.0 - Vector2::repeat(1.0);
+
+        let unproject = |z|
+        {
+            let point = self.projection_view_inverse * Vector4::new(ndc.x, ndc.y, z, 1.0);
+
+            point.xyz() / point.w
+        };
+
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+
+        (near, (far - near).normalize())
+    }
+
+    pub fn screen_to_world(&self, normalized: Vector2<f32>) -> Vector2<f32>
+    {
+        self.screen_to_ray(normalized).0.xy()
+    }
+
+    pub fn matrices(&self) -> CameraMatrices
+    {
+        CameraMatrices{
+            projection_view: self.projection_view.into(),
+            projection_view_inverse: self.projection_view_inverse.into()
+        }
+    }
+
     pub fn resize(&mut self, aspect: f32)
     {
         self.aspect = aspect;
@@ -180,6 +272,54 @@ impl Camera
         self.size
     }
 
+    pub fn visible_rect(&self) -> (Vector2<f32>, Vector2<f32>)
+    {
+        let center = self.position().coords.xy();
+        let half = self.size / 2.0;
+
+        (center - half, center + half)
+    }
+
+    // world space aabb of everything on screen, includes the camera rotation
+    pub fn visible_bounds(&self) -> (Vector2<f32>, Vector2<f32>)
+    {
+        let center = self.position().coords.xy();
+        let half = self.size / 2.0;
+
+        let rotation = self.rotation();
+        let (sin, cos) = (rotation.sin().abs(), rotation.cos().abs());
+
+        let extent = Vector2::new(
+            half.x * cos + half.y * sin,
+            half.x * sin + half.y * cos
+        );
+
+        (center - extent, center + extent)
+    }
+
+    // tests a bounding sphere against the planes of the projection view frustum
+    pub fn is_visible(&self, position: Vector3<f32>, radius: f32) -> bool
+    {
+        let matrix = &self.projection_view;
+
+        let last = matrix.row(3);
+        let mut planes = (0..3).flat_map(|index|
+        {
+            let row = matrix.row(index);
+
+            [last + row, last - row]
+        });
+
+        let point = Vector4::new(position.x, position.y, position.z, 1.0);
+
+        planes.all(|plane|
+        {
+            let plane = plane.transpose();
+
+            plane.dot(&point) >= -radius * plane.xyz().magnitude()
+        })
+    }
+
     pub fn over_size(&self) -> Vector2<f32>
     {
         let lowest = self.size.x.min(self.size.y);
@@ -194,3 +334,101 @@ impl Camera
         self.size / highest
     }
 }
+
+// axis aligned box for picking with the ray from Camera::screen_to_ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb
+{
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>
+}
+
+impl Aabb
+{
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self
+    {
+        Self{min, max}
+    }
+
+    pub fn from_center(center: Vector3<f32>, size: Vector3<f32>) -> Self
+    {
+        let half = size / 2.0;
+
+        Self{min: center - half, max: center + half}
+    }
+
+    // distance along the ray to where it enters the box (0 if it starts inside),
+    // none if it misses or the box is behind it
+    pub fn intersects_ray(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<f32>
+    {
+        let mut near = 0.0_f32;
+        let mut far = f32::INFINITY;
+
+        for axis in 0..3
+        {
+            if direction[axis] == 0.0
+            {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis]
+                {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let inverse = direction[axis].recip();
+
+            let a = (self.min[axis] - origin[axis]) * inverse;
+            let b = (self.max[axis] - origin[axis]) * inverse;
+
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+
+            if near > far
+            {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn ray_picking()
+    {
+        let mut camera = Camera::new(1.0, -1.0..1.0);
+        camera.set_position(Point3::new(2.0, 3.0, 0.0));
+        camera.update();
+
+        let (origin, direction) = camera.screen_to_ray(Vector2::new(0.5, 0.5));
+
+        assert!((origin.xy() - Vector2::new(2.0, 3.0)).magnitude() < 0.0001);
+
+        let ahead = origin + direction * 0.5;
+
+        let under_center = Aabb::from_center(ahead, Vector3::repeat(0.1));
+        let distance = under_center.intersects_ray(origin, direction).unwrap();
+        assert!((distance - 0.45).abs() < 0.0001);
+
+        let to_the_side = Aabb::from_center(ahead + Vector3::x() * 0.3, Vector3::repeat(0.1));
+        assert_eq!(to_the_side.intersects_ray(origin, direction), None);
+
+        let behind = Aabb::from_center(origin - direction * 0.5, Vector3::repeat(0.1));
+        assert_eq!(behind.intersects_ray(origin, direction), None);
+
+        // the top right corner of the screen is half the view size away
+        let (corner_origin, corner_direction) = camera.screen_to_ray(Vector2::new(1.0, 1.0));
+        let corner = Aabb::from_center(
+            Vector3::new(2.5, 3.5, ahead.z),
+            Vector3::repeat(0.1)
+        );
+
+        assert!(corner.intersects_ray(corner_origin, corner_direction).is_some());
+    }
+}
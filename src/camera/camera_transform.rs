@@ -1,14 +1,18 @@
 use nalgebra::{
+    Unit,
     Vector3,
     Point3,
+    Rotation3,
     base::Matrix4
 };
 
 
+#[derive(Debug, Clone)]
 pub struct CameraTransformConfig
 {
     pub position: Point3<f32>,
-    pub forward: Vector3<f32>
+    pub forward: Vector3<f32>,
+    pub up: Vector3<f32>
 }
 
 impl Default for CameraTransformConfig
@@ -17,7 +21,8 @@ impl Default for CameraTransformConfig
     {
         Self{
             position: Point3::new(0.0, 0.0, 0.0),
-            forward: Vector3::z()
+            forward: Vector3::z(),
+            up: Vector3::y()
         }
     }
 }
@@ -30,6 +35,7 @@ pub struct CameraTransform
     forward: Vector3<f32>,
     up: Vector3<f32>,
     right: Vector3<f32>,
+    rotation: f32,
     matrix: Matrix4<f32>
 }
 
@@ -38,24 +44,23 @@ impl CameraTransform
 {
     pub fn new(config: CameraTransformConfig) -> Self
     {
-        let right = Self::calculate_right(&config.forward);
+        let right = Self::calculate_right(&config.forward, &config.up);
         let up = Self::calculate_up(&config.forward, &right);
 
-        let matrix = Self::calculate_matrix(&config.position, &config.forward, &up);
+        let matrix = Self::calculate_matrix(&config.position, &config.forward, &up, 0.0);
 
         Self{
             position: config.position,
             forward: config.forward,
             up,
             right,
+            rotation: 0.0,
             matrix
         }
     }
 
-    fn calculate_right(forward: &Vector3<f32>) -> Vector3<f32>
+    fn calculate_right(forward: &Vector3<f32>, global_up: &Vector3<f32>) -> Vector3<f32>
     {
-        let global_up = Vector3::y();
-
         global_up.cross(forward).normalize()
     }
 
@@ -64,6 +69,16 @@ impl CameraTransform
         forward.cross(right).normalize()
     }
 
+    pub fn forward(&self) -> &Vector3<f32>
+    {
+        &self.forward
+    }
+
+    pub fn up(&self) -> &Vector3<f32>
+    {
+        &self.up
+    }
+
     pub fn position(&self) -> &Point3<f32>
     {
         &self.position
@@ -100,20 +115,34 @@ impl CameraTransform
         self.position.coords = self.position.coords.lerp(other, amount);
     }
 
+    pub fn rotation(&self) -> f32
+    {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32)
+    {
+        self.rotation = rotation;
+    }
+
     pub fn update(&mut self)
     {
-        self.matrix = Self::calculate_matrix(&self.position, &self.forward, &self.up);
+        self.matrix = Self::calculate_matrix(&self.position, &self.forward, &self.up, self.rotation);
     }
 
     fn calculate_matrix(
         position: &Point3<f32>,
         forward: &Vector3<f32>,
-        up: &Vector3<f32>
+        up: &Vector3<f32>,
+        rotation: f32
     ) -> Matrix4<f32>
     {
         let target = *position + forward;
 
-        Matrix4::look_at_lh(position, &target, up)
+        // rolls the camera around the view axis
+        let up = Rotation3::from_axis_angle(&Unit::new_normalize(*forward), rotation) * up;
+
+        Matrix4::look_at_lh(position, &target, &up)
     }
 
     pub fn matrix(&self) -> Matrix4<f32>
@@ -0,0 +1,82 @@
+use std::{
+    fmt,
+    cell::RefCell,
+    sync::Arc
+};
+
+use vulkano::{
+    device::Queue,
+    sync::{GpuFuture, Sharing}
+};
+
+
+pub struct ComputeQueue
+{
+    queue: Arc<Queue>,
+    graphics_family: u32,
+    dedicated: bool,
+    pending: RefCell<Vec<Box<dyn GpuFuture>>>
+}
+
+impl ComputeQueue
+{
+    pub fn new(queue: Arc<Queue>, graphics_family: u32) -> Self
+    {
+        let dedicated = queue.queue_family_index() != graphics_family;
+
+        Self{queue, graphics_family, dedicated, pending: RefCell::new(Vec::new())}
+    }
+
+    pub fn queue(&self) -> &Arc<Queue>
+    {
+        &self.queue
+    }
+
+    pub fn is_dedicated(&self) -> bool
+    {
+        self.dedicated
+    }
+
+    pub fn queue_families(&self) -> [u32; 2]
+    {
+        [self.graphics_family, self.queue.queue_family_index()]
+    }
+
+    // buffers and images written by compute and then used for rendering (or the other way)
+    // have to be created with this, theres no ownership transfer between the queue families
+    pub fn sharing<I>(&self) -> Sharing<I>
+    where
+        I: FromIterator<u32> + IntoIterator<Item=u32>
+    {
+        if self.dedicated
+        {
+            Sharing::Concurrent(self.queue_families().into_iter().collect())
+        } else
+        {
+            Sharing::Exclusive
+        }
+    }
+
+    // the next graphics submission waits on this future
+    pub fn submit<F: GpuFuture + 'static>(&self, future: F)
+    {
+        self.pending.borrow_mut().push(Box::new(future));
+    }
+
+    pub(crate) fn take_pending(&self) -> Vec<Box<dyn GpuFuture>>
+    {
+        self.pending.take()
+    }
+}
+
+impl fmt::Debug for ComputeQueue
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("ComputeQueue")
+            .field("queue", &self.queue)
+            .field("graphics_family", &self.graphics_family)
+            .field("dedicated", &self.dedicated)
+            .finish()
+    }
+}
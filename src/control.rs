@@ -6,7 +6,7 @@ use std::{
 use serde::{ser::{self, Error, Impossible}, Serialize};
 
 use winit::{
-    event::{ElementState, MouseButton},
+    event::{ElementState, MouseButton, TouchPhase},
     keyboard::{Key, KeyCode, PhysicalKey}
 };
 
@@ -188,10 +188,65 @@ impl Display for KeyCodeNamed
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton
+{
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Unknown
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis
+{
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftZ,
+    RightZ,
+    DPadX,
+    DPadY,
+    Unknown
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadInput
+{
+    Connected,
+    Disconnected,
+    Button{button: GamepadButton, state: ElementState},
+    // value is always in -1.0..=1.0
+    Axis{axis: GamepadAxis, value: f32}
+}
+
 #[derive(Debug, Clone)]
 pub enum Control
 {
-    Keyboard{logical: Key, keycode: PhysicalKey, state: ElementState},
+    // repeat is true for presses generated by holding the key down
+    Keyboard{logical: Key, keycode: PhysicalKey, state: ElementState, repeat: bool},
     Mouse{button: MouseButton, state: ElementState},
-    Scroll{x: f64, y: f64}
+    Scroll{x: f64, y: f64},
+    // position is in 0..1 screen space like the mouse position, ids are unique per active touch
+    Touch{id: u64, phase: TouchPhase, position: (f64, f64)},
+    Gamepad{id: usize, input: GamepadInput},
+    // typed text (including dead keys) and committed ime text
+    Text{text: String},
+    // ime composition in progress, cursor is a byte range into the text
+    ImePreedit{text: String, cursor: Option<(usize, usize)>}
 }
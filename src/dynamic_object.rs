@@ -0,0 +1,225 @@
+use std::{
+    mem,
+    fmt,
+    sync::Arc
+};
+
+use parking_lot::RwLock;
+
+use vulkano::{
+    buffer::Subbuffer,
+    pipeline::{
+        PipelineBindPoint,
+        graphics::vertex_input::VertexBufferDescription
+    }
+};
+
+use nalgebra::{Vector3, Vector4, Matrix4};
+
+use crate::{
+    game_object::*,
+    object::{impl_updated_check, NormalGraphicalObject, ObjectTransform, ObjectVertex, Model, Texture},
+    allocators::{ObjectAllocator, FrameSubbuffers},
+    transform::{Transform, OnTransformCallback, TransformContainer}
+};
+
+
+// like an object but the model can change its vertex count, the buffer only gets
+// reallocated when the model outgrows it
+pub struct DynamicObject
+{
+    model: Model,
+    texture: Arc<RwLock<Texture>>,
+    transform: ObjectTransform,
+    growth: f32,
+    subbuffers: FrameSubbuffers<ObjectVertex>,
+    #[cfg(debug_assertions)]
+    updated_buffers: bool
+}
+
+impl NormalGraphicalObject<ObjectVertex> for DynamicObject
+{
+    fn subbuffer(&self, frame_parity: bool) -> Subbuffer<[ObjectVertex]>
+    {
+        self.subbuffers[frame_parity as usize].clone().slice(0..self.model.vertices.len() as u64)
+    }
+
+    fn vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
+    {
+        self.calculate_vertices(projection_view)
+    }
+
+    impl_updated_check!{}
+}
+
+#[allow(dead_code)]
+impl DynamicObject
+{
+    pub fn new(
+        model: Model,
+        texture: Arc<RwLock<Texture>>,
+        transform: ObjectTransform,
+        capacity: usize,
+        allocator: &ObjectAllocator
+    ) -> Self
+    {
+        let capacity = capacity.max(model.vertices.len()).max(1);
+        let subbuffers = allocator.frame_subbuffers(capacity as u64);
+
+        Self{
+            model,
+            texture,
+            transform,
+            growth: 2.0,
+            subbuffers,
+            #[cfg(debug_assertions)]
+            updated_buffers: false
+        }
+    }
+
+    fn calculate_vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
+    {
+        let transform = self.transform.matrix();
+
+        self.model.vertices.iter().zip(self.model.uvs.iter()).map(move |(vertex, uv)|
+        {
+            let vertex = Vector4::new(vertex[0], vertex[1], vertex[2], 1.0);
+
+            let vertex = projection_view * transform * vertex;
+
+            ObjectVertex{position: vertex.into(), uv: *uv}
+        }).collect::<Box<[_]>>()
+    }
+
+    pub fn capacity(&self) -> usize
+    {
+        self.subbuffers[0].len() as usize
+    }
+
+    // how much the capacity gets multiplied by when the model doesnt fit
+    pub fn set_growth(&mut self, growth: f32)
+    {
+        self.growth = growth.max(1.0);
+    }
+
+    pub fn model(&self) -> &Model
+    {
+        &self.model
+    }
+
+    pub fn set_model(&mut self, model: Model)
+    {
+        self.model = model;
+    }
+
+    pub fn set_origin(&mut self, origin: Vector3<f32>)
+    {
+        self.transform.set_origin(origin);
+    }
+
+    pub fn set_texture(&mut self, texture: Arc<RwLock<Texture>>)
+    {
+        self.texture = texture;
+    }
+
+    pub fn texture(&self) -> &Arc<RwLock<Texture>>
+    {
+        &self.texture
+    }
+
+    fn reserve(&mut self, allocator: &ObjectAllocator)
+    {
+        let required = self.model.vertices.len();
+        if required <= self.capacity()
+        {
+            return;
+        }
+
+        let grown = (self.capacity() as f32 * self.growth).ceil() as usize;
+
+        let subbuffers = allocator.frame_subbuffers(grown.max(required) as u64);
+        mem::replace(&mut self.subbuffers, subbuffers).into_iter().for_each(|subbuffer|
+        {
+            allocator.recycle(subbuffer);
+        });
+    }
+
+    pub fn per_vertex() -> VertexBufferDescription
+    {
+        crate::Object::per_vertex()
+    }
+}
+
+impl GameObject for DynamicObject
+{
+    fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let object_factory = info.partial.object_factory.clone();
+        self.reserve(object_factory.allocator());
+
+        self.normal_update_buffers(info);
+    }
+
+    fn draw(&self, info: &mut DrawInfo)
+    {
+        if self.model.vertices.is_empty()
+        {
+            return;
+        }
+
+        self.assert_updated(&info.object_info);
+
+        let (set, descriptor_set) = {
+            let texture = self.texture.read();
+
+            (texture.location().set, texture.descriptor_set())
+        };
+
+        let layout = info.current_layout();
+        info.object_info.builder_wrapper.builder()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                layout,
+                set,
+                descriptor_set
+            )
+            .unwrap()
+            .bind_vertex_buffers(0, self.subbuffer(info.object_info.frame_parity))
+            .unwrap()
+            .draw(self.model.vertices.len() as u32, 1, 0, 0)
+            .unwrap();
+    }
+}
+
+impl OnTransformCallback for DynamicObject
+{
+    fn callback(&mut self)
+    {
+        self.transform.callback();
+    }
+}
+
+impl TransformContainer for DynamicObject
+{
+    fn transform_ref(&self) -> &Transform
+    {
+        self.transform.transform_ref()
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform
+    {
+        self.transform.transform_mut()
+    }
+}
+
+impl fmt::Debug for DynamicObject
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("DynamicObject")
+            .field("model", &self.model)
+            .field("transform", &self.transform)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
@@ -1,21 +1,28 @@
 use std::{
     rc::Rc,
+    cell::Cell,
     sync::Arc
 };
 
 use parking_lot::Mutex;
 
+use nalgebra::Vector2;
+
 use vulkano::{
     device::Device,
     memory::allocator::StandardMemoryAllocator
 };
 
+use winit::window::Window;
+
 use crate::{
     ObjectFactory,
-    AssetsPaths,
-    ShadersQuery,
+    AppOptions,
     Assets,
-    UniformLocation,
+    ComputeQueue,
+    FrameStats,
+    CaptureError,
+    render_target::OffscreenCreator,
     allocators::{UniformAllocator, ObjectAllocator},
     text_factory::FontsContainer,
     game_object::*,
@@ -28,24 +35,37 @@ pub struct Engine
     fonts_info: Rc<FontsContainer>,
     object_factory: Rc<ObjectFactory>,
     uniform_allocator: Rc<UniformAllocator>,
+    compute: Rc<ComputeQueue>,
+    window: Option<Arc<Window>>,
+    mouse_position: Vector2<f32>,
+    capture: Rc<Cell<bool>>,
+    capture_error: Option<CaptureError>,
+    clear_color: Rc<Cell<Option<[f32; 4]>>>,
+    offscreen: OffscreenCreator,
+    frame_stats: FrameStats,
     assets: Arc<Mutex<Assets>>
 }
 
 impl Engine
 {
     pub fn new(
-        assets_paths: &AssetsPaths,
+        options: &mut AppOptions,
         mut resource_uploader: ResourceUploader,
         device: Arc<Device>,
-        shaders_query: ShadersQuery
+        compute: ComputeQueue,
+        offscreen: OffscreenCreator,
+        capture_error: Option<CaptureError>,
+        window: Option<Arc<Window>>
     ) -> Self
     {
         let assets = Assets::new(
             &mut resource_uploader,
-            assets_paths.textures.as_ref(),
-            assets_paths.models.as_ref(),
-            UniformLocation{set: 0, binding: 0},
-            shaders_query
+            options.assets_paths.textures.as_ref(),
+            options.assets_paths.models.as_ref(),
+            options.background_textures,
+            options.background_models,
+            options.texture_location,
+            options.shaders_query.take().unwrap()
         );
 
         let assets = Arc::new(Mutex::new(assets));
@@ -57,16 +77,56 @@ impl Engine
         let object_factory = ObjectFactory::new(allocator);
         let object_factory = Rc::new(object_factory);
 
-        let fonts_info = Rc::new(FontsContainer::new());
+        let fonts_info = Rc::new(options.fonts.take().unwrap_or_else(FontsContainer::new));
+
+        let compute = Rc::new(compute);
 
-        Self{fonts_info, object_factory, uniform_allocator, assets}
+        Self{
+            fonts_info,
+            object_factory,
+            uniform_allocator,
+            compute,
+            window,
+            mouse_position: Vector2::zeros(),
+            capture: Rc::new(Cell::new(false)),
+            capture_error,
+            clear_color: Rc::new(Cell::new(None)),
+            offscreen,
+            frame_stats: FrameStats::new(),
+            assets
+        }
+    }
+
+    // rebuilds everything that lived on the old device, assets keep their ids and get
+    // reloaded in place so the handles the app already has stay valid
+    pub fn recreate(
+        &mut self,
+        mut resource_uploader: ResourceUploader,
+        device: Arc<Device>,
+        compute: ComputeQueue,
+        offscreen: OffscreenCreator,
+        capture_error: Option<CaptureError>
+    )
+    {
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+        let allocator = ObjectAllocator::new(memory_allocator.clone());
+
+        self.uniform_allocator = Rc::new(UniformAllocator::new(memory_allocator));
+        self.object_factory = Rc::new(ObjectFactory::new(allocator));
+        self.compute = Rc::new(compute);
+        self.offscreen = offscreen;
+        self.capture_error = capture_error;
+
+        self.fonts_info.clear_text_cache();
+
+        self.assets.lock().reload_textures(&mut resource_uploader);
     }
 
-    #[allow(unused_variables)]
     pub fn object_create_partial_info<'a>(
         &'a mut self,
         resource_uploader: ResourceUploader<'a>,
         size: [f32; 2],
+        frame: FrameInfo,
         frame_parity: bool
     ) -> ObjectCreatePartialInfo<'a>
     {
@@ -81,8 +141,16 @@ impl Engine
             assets: self.assets.clone(),
             object_factory: self.object_factory.clone(),
             uniform_allocator: self.uniform_allocator.clone(),
+            compute: self.compute.clone(),
             size,
-            #[cfg(debug_assertions)]
+            frame,
+            frame_stats: &self.frame_stats,
+            mouse_position: self.mouse_position,
+            capture: self.capture.clone(),
+            capture_error: self.capture_error,
+            clear_color: self.clear_color.clone(),
+            offscreen: self.offscreen.clone(),
+            window: self.window.clone(),
             frame_parity
         }
     }
@@ -90,14 +158,61 @@ impl Engine
     pub fn init_partial_info<'a>(
         &'a mut self,
         resource_uploader: ResourceUploader<'a>,
-        size: [f32; 2]
+        size: [f32; 2],
+        frame: FrameInfo
     ) -> InitPartialInfo<'a>
     {
-        self.object_create_partial_info(resource_uploader, size, false)
+        self.object_create_partial_info(resource_uploader, size, frame, false)
+    }
+
+    pub fn compute(&self) -> &Rc<ComputeQueue>
+    {
+        &self.compute
+    }
+
+    pub fn set_mouse_position(&mut self, position: Vector2<f32>)
+    {
+        self.mouse_position = position;
+    }
+
+    pub fn push_frame_time(&mut self, dt: f32)
+    {
+        self.frame_stats.push(dt);
+    }
+
+    pub fn clear_text_cache(&self)
+    {
+        self.fonts_info.clear_text_cache();
+    }
+
+    pub fn reset_transient(&self)
+    {
+        self.object_factory.allocator().reset();
+    }
+
+    pub fn request_capture(&self)
+    {
+        self.capture.set(true);
+    }
+
+    pub fn take_capture_request(&self) -> bool
+    {
+        self.capture.replace(false)
+    }
+
+    pub fn take_clear_color(&self) -> Option<[f32; 4]>
+    {
+        self.clear_color.take()
+    }
+
+    pub fn poll_assets(&mut self, resource_uploader: &mut ResourceUploader)
+    {
+        self.assets.lock().poll_loading(resource_uploader);
     }
 
     pub fn swap_pipelines(&mut self, resource_uploader: &ResourceUploader)
     {
         self.assets.lock().swap_pipelines(resource_uploader);
+        self.fonts_info.swap_pipelines(resource_uploader);
     }
 }
@@ -0,0 +1,82 @@
+const HISTORY: usize = 120;
+
+// frame times in seconds over the last HISTORY frames
+#[derive(Debug, Clone)]
+pub struct FrameStats
+{
+    times: [f32; HISTORY],
+    head: usize,
+    len: usize
+}
+
+impl Default for FrameStats
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl FrameStats
+{
+    pub fn new() -> Self
+    {
+        Self{
+            times: [0.0; HISTORY],
+            head: 0,
+            len: 0
+        }
+    }
+
+    pub fn push(&mut self, dt: f32)
+    {
+        self.times[self.head] = dt;
+
+        self.head = (self.head + 1) % HISTORY;
+        self.len = (self.len + 1).min(HISTORY);
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    // oldest to newest
+    pub fn times(&self) -> impl Iterator<Item=f32> + '_
+    {
+        let start = (self.head + HISTORY - self.len) % HISTORY;
+
+        (0..self.len).map(move |index| self.times[(start + index) % HISTORY])
+    }
+
+    pub fn last(&self) -> Option<f32>
+    {
+        (!self.is_empty()).then(|| self.times[(self.head + HISTORY - 1) % HISTORY])
+    }
+
+    pub fn min(&self) -> Option<f32>
+    {
+        self.times().reduce(f32::min)
+    }
+
+    pub fn max(&self) -> Option<f32>
+    {
+        self.times().reduce(f32::max)
+    }
+
+    pub fn average(&self) -> Option<f32>
+    {
+        (!self.is_empty()).then(|| self.times().sum::<f32>() / self.len as f32)
+    }
+
+    // based on the average so it doesnt jump around every frame
+    pub fn fps(&self) -> Option<f32>
+    {
+        self.average().filter(|average| *average > 0.0).map(f32::recip)
+    }
+}
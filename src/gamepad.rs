@@ -0,0 +1,106 @@
+use gilrs::{Gilrs, EventType, Button, Axis};
+
+use winit::event::ElementState;
+
+use crate::control::{Control, GamepadInput, GamepadButton, GamepadAxis};
+
+
+impl From<Button> for GamepadButton
+{
+    fn from(value: Button) -> Self
+    {
+        match value
+        {
+            Button::South => Self::South,
+            Button::East => Self::East,
+            Button::North => Self::North,
+            Button::West => Self::West,
+            Button::LeftTrigger => Self::LeftBumper,
+            Button::RightTrigger => Self::RightBumper,
+            Button::LeftTrigger2 => Self::LeftTrigger,
+            Button::RightTrigger2 => Self::RightTrigger,
+            Button::Select => Self::Select,
+            Button::Start => Self::Start,
+            Button::Mode => Self::Mode,
+            Button::LeftThumb => Self::LeftThumb,
+            Button::RightThumb => Self::RightThumb,
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            _ => Self::Unknown
+        }
+    }
+}
+
+impl From<Axis> for GamepadAxis
+{
+    fn from(value: Axis) -> Self
+    {
+        match value
+        {
+            Axis::LeftStickX => Self::LeftStickX,
+            Axis::LeftStickY => Self::LeftStickY,
+            Axis::RightStickX => Self::RightStickX,
+            Axis::RightStickY => Self::RightStickY,
+            Axis::LeftZ => Self::LeftZ,
+            Axis::RightZ => Self::RightZ,
+            Axis::DPadX => Self::DPadX,
+            Axis::DPadY => Self::DPadY,
+            _ => Self::Unknown
+        }
+    }
+}
+
+pub struct Gamepads
+{
+    gilrs: Option<Gilrs>
+}
+
+impl Gamepads
+{
+    pub fn new() -> Self
+    {
+        let gilrs = Gilrs::new().map_err(|err|
+        {
+            eprintln!("gamepad support disabled: {err}");
+        }).ok();
+
+        Self{gilrs}
+    }
+
+    pub fn poll(&mut self, mut f: impl FnMut(Control))
+    {
+        let gilrs = if let Some(x) = self.gilrs.as_mut()
+        {
+            x
+        } else
+        {
+            return;
+        };
+
+        while let Some(event) = gilrs.next_event()
+        {
+            let input = match event.event
+            {
+                EventType::Connected => GamepadInput::Connected,
+                EventType::Disconnected => GamepadInput::Disconnected,
+                EventType::ButtonPressed(button, _) => GamepadInput::Button{
+                    button: button.into(),
+                    state: ElementState::Pressed
+                },
+                EventType::ButtonReleased(button, _) => GamepadInput::Button{
+                    button: button.into(),
+                    state: ElementState::Released
+                },
+                EventType::AxisChanged(axis, value, _) => GamepadInput::Axis{
+                    axis: axis.into(),
+                    value: value.clamp(-1.0, 1.0)
+                },
+                _ => continue
+            };
+
+            f(Control::Gamepad{id: usize::from(event.id), input});
+        }
+    }
+}
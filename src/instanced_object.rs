@@ -0,0 +1,221 @@
+use std::{
+    mem,
+    fmt,
+    sync::Arc
+};
+
+use parking_lot::RwLock;
+
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    pipeline::{
+        PipelineBindPoint,
+        graphics::vertex_input::{VertexBufferDescription, Vertex}
+    }
+};
+
+use crate::{
+    game_object::*,
+    object::{ObjectTransform, ObjectVertex, Model, Texture},
+    allocators::ObjectAllocator,
+    transform::{Transform, TransformContainer}
+};
+
+
+#[derive(BufferContents, Vertex, Clone, Copy)]
+#[repr(C)]
+struct InstanceData
+{
+    #[format(R32G32B32A32_SFLOAT)]
+    instance_matrix: [[f32; 4]; 4]
+}
+
+// draws the same model and texture at every transform in one draw call,
+// needs a shader made with Shader::instanced
+pub struct InstancedObject
+{
+    model: Arc<RwLock<Model>>,
+    texture: Arc<RwLock<Texture>>,
+    transforms: Vec<ObjectTransform>,
+    vertices: Subbuffer<[ObjectVertex]>,
+    instances: Subbuffer<[InstanceData]>,
+    #[cfg(debug_assertions)]
+    updated_buffers: bool
+}
+
+impl InstancedObject
+{
+    pub fn new(
+        model: Arc<RwLock<Model>>,
+        texture: Arc<RwLock<Texture>>,
+        transforms: &[Transform],
+        allocator: &ObjectAllocator
+    ) -> Self
+    {
+        let vertices = allocator.subbuffer(model.read().vertices.len().max(1) as u64);
+        let instances = allocator.subbuffer(transforms.len().max(1) as u64);
+
+        let transforms = transforms.iter().cloned().map(ObjectTransform::new_transformed).collect();
+
+        Self{
+            model,
+            texture,
+            transforms,
+            vertices,
+            instances,
+            #[cfg(debug_assertions)]
+            updated_buffers: false
+        }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.transforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.transforms.is_empty()
+    }
+
+    pub fn transform(&self, index: usize) -> &Transform
+    {
+        self.transforms[index].transform_ref()
+    }
+
+    pub fn set_transform(&mut self, index: usize, transform: Transform)
+    {
+        self.transforms[index] = ObjectTransform::new_transformed(transform);
+    }
+
+    pub fn set_transforms(&mut self, transforms: &[Transform])
+    {
+        self.transforms = transforms.iter().cloned().map(ObjectTransform::new_transformed).collect();
+    }
+
+    pub fn texture(&self) -> &Arc<RwLock<Texture>>
+    {
+        &self.texture
+    }
+
+    pub fn set_texture(&mut self, texture: Arc<RwLock<Texture>>)
+    {
+        self.texture = texture;
+    }
+
+    fn reserve(&mut self, allocator: &ObjectAllocator)
+    {
+        let vertices = self.model.read().vertices.len();
+        if vertices > self.vertices.len() as usize
+        {
+            let subbuffer = allocator.subbuffer(vertices as u64);
+            allocator.recycle(mem::replace(&mut self.vertices, subbuffer));
+        }
+
+        if self.transforms.len() > self.instances.len() as usize
+        {
+            let subbuffer = allocator.subbuffer(self.transforms.len() as u64);
+            allocator.recycle(mem::replace(&mut self.instances, subbuffer));
+        }
+    }
+
+    fn needs_draw(&self) -> bool
+    {
+        !self.transforms.is_empty() && !self.model.read().vertices.is_empty()
+    }
+
+    pub fn per_vertex() -> VertexBufferDescription
+    {
+        ObjectVertex::per_vertex()
+    }
+
+    pub fn per_instance() -> VertexBufferDescription
+    {
+        InstanceData::per_instance()
+    }
+
+    crate::impl_updated_check!{}
+}
+
+impl GameObject for InstancedObject
+{
+    fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        if !self.needs_draw()
+        {
+            return;
+        }
+
+        let object_factory = info.partial.object_factory.clone();
+        self.reserve(object_factory.allocator());
+
+        self.set_updated(&info.partial);
+
+        // the model stays in model space, the instance matrices move it
+        let vertices = {
+            let model = self.model.read();
+
+            model.vertices.iter().zip(model.uvs.iter()).map(|(vertex, uv)|
+            {
+                ObjectVertex{position: [vertex[0], vertex[1], vertex[2], 1.0], uv: *uv}
+            }).collect::<Vec<_>>()
+        };
+
+        let instances = self.transforms.iter().map(|transform|
+        {
+            InstanceData{instance_matrix: (info.projection_view * transform.matrix()).into()}
+        }).collect::<Vec<_>>();
+
+        let builder_wrapper = &mut info.partial.builder_wrapper;
+
+        builder_wrapper.update_buffer(&self.vertices, &vertices);
+        builder_wrapper.update_buffer(&self.instances, &instances);
+    }
+
+    fn draw(&self, info: &mut DrawInfo)
+    {
+        if !self.needs_draw()
+        {
+            return;
+        }
+
+        self.assert_updated(&info.object_info);
+
+        let size = self.model.read().vertices.len() as u32;
+        let count = self.transforms.len() as u32;
+
+        let (set, descriptor_set) = {
+            let texture = self.texture.read();
+
+            (texture.location().set, texture.descriptor_set())
+        };
+
+        let layout = info.current_layout();
+        info.object_info.builder_wrapper.builder()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                layout,
+                set,
+                descriptor_set
+            )
+            .unwrap()
+            .bind_vertex_buffers(0, (
+                self.vertices.clone().slice(0..size as u64),
+                self.instances.clone().slice(0..count as u64)
+            ))
+            .unwrap()
+            .draw(size, count, 0, 0)
+            .unwrap();
+    }
+}
+
+impl fmt::Debug for InstancedObject
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("InstancedObject")
+            .field("model", &self.model)
+            .field("instances", &self.transforms.len())
+            .finish()
+    }
+}
@@ -3,22 +3,29 @@
 #![allow(clippy::new_without_default)]
 
 use std::{
-    fmt::Display,
+    error,
+    fmt::{self, Display},
     marker::PhantomData,
     path::{Path, PathBuf},
+    rc::Rc,
     sync::Arc
 };
 
 use vulkano::{
     VulkanLibrary,
     buffer::subbuffer::BufferContents,
-    swapchain::Surface,
+    format::{Format, ClearValue},
+    image::SampleCount,
+    swapchain::{Surface, ColorSpace},
+    command_buffer::SubpassContents,
     pipeline::{
         PipelineLayout,
         PipelineShaderStageCreateInfo,
         graphics::{
             vertex_input::{VertexBufferDescription, Vertex},
-            depth_stencil::{DepthState, StencilState}
+            depth_stencil::{DepthState, StencilState},
+            color_blend::AttachmentBlend,
+            input_assembly::PrimitiveTopology
         },
         layout::PipelineDescriptorSetLayoutCreateInfo
     },
@@ -27,7 +34,6 @@ use vulkano::{
         Device,
         DeviceCreateInfo,
         DeviceExtensions,
-        Queue,
         QueueFlags,
         QueueCreateInfo,
         physical::{
@@ -35,36 +41,73 @@ use vulkano::{
             PhysicalDeviceType
         }
     },
-    instance::{Instance, InstanceCreateInfo}
+    instance::{
+        Instance,
+        InstanceCreateInfo,
+        InstanceExtensions,
+        debug::{
+            DebugUtilsMessenger,
+            DebugUtilsMessengerCallback,
+            DebugUtilsMessengerCreateInfo,
+            DebugUtilsMessageSeverity,
+            DebugUtilsMessageType
+        }
+    }
 };
 
 use winit::{
-    window::{Icon, WindowBuilder},
+    dpi::PhysicalSize,
+    monitor::MonitorHandle,
+    window::{Icon, Fullscreen, CursorGrabMode, WindowBuilder},
     event_loop::{DeviceEvents, EventLoop}
 };
 
-use window::{GraphicsInfo, PipelineCreateInfo};
-pub use window::{Rendering, PipelineInfo};
+use window::{GraphicsInfo, GraphicsCreator, PipelineCreateInfo, Queues};
+pub use window::{Rendering, RenderingConfig, PipelineInfo, CaptureError};
 
 use game_object::*;
 
 pub use object::{
     Object,
     game_object,
-    resource_uploader::ResourceUploader
+    resource_uploader::ResourceUploader,
+    texture::RgbaImage
 };
 
 pub use solid_object::SolidObject;
 
-pub use occluding_plane::OccludingPlane;
+pub use dynamic_object::DynamicObject;
+pub use instanced_object::InstancedObject;
+
+pub use sprite_batch::SpriteBatch;
+
+pub use sdf_object::{SdfObject, SdfParams};
+
+pub use parallax_layer::ParallaxLayer;
+
+pub use atlas::{Atlas, PackedAtlas, UvRect};
 
-pub use text_object::{TextAlign, VerticalAlign, HorizontalAlign, TextObject};
-pub use text_factory::{TextInfo, TextCreateInfo, FontStyle, FontsContainer};
+pub use render_target::{RenderTargetImages, OffscreenTarget};
+
+pub use frame_stats::FrameStats;
+
+pub use occluding_plane::{OccludingPlane, OccluderPoints, ShadowCaster};
+
+pub use text_object::{TextAlign, VerticalAlign, HorizontalAlign, TextObject, TextLayout, LineRect};
+pub use text_factory::{
+    TextInfo,
+    TextInfoBlock,
+    TextBlocks,
+    TextOutline,
+    TextCreateInfo,
+    FontStyle,
+    FontsContainer
+};
 
 pub use nalgebra::Vector3;
 pub use winit::{
     keyboard::{PhysicalKey, Key, KeyCode, NamedKey},
-    event::{ElementState, MouseButton}
+    event::{ElementState, MouseButton, TouchPhase}
 };
 
 pub use transform::{
@@ -75,18 +118,34 @@ pub use transform::{
 
 pub use allocators::UniformLocation;
 
+pub use compute::ComputeQueue;
+pub use transfer::TransferQueue;
+
 pub use object_factory::{ObjectFactory, ObjectInfo};
 pub use assets::*;
 
-pub use control::{KeyCodeNamed, Control};
+pub use control::{KeyCodeNamed, Control, GamepadInput, GamepadButton, GamepadAxis};
 
 mod control;
 
+#[cfg(feature = "gamepad")]
+mod gamepad;
+
 pub mod allocators;
+pub mod compute;
+pub mod transfer;
 
 pub mod occluding_plane;
 pub mod object;
 pub mod solid_object;
+pub mod dynamic_object;
+pub mod instanced_object;
+pub mod sprite_batch;
+pub mod sdf_object;
+pub mod parallax_layer;
+pub mod atlas;
+pub mod render_target;
+pub mod frame_stats;
 pub mod camera;
 pub mod transform;
 
@@ -101,6 +160,8 @@ mod window;
 
 pub const WINDING_MATTERS: bool = false;
 
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
 mod default_vertex
 {
     vulkano_shaders::shader!
@@ -110,6 +171,15 @@ mod default_vertex
     }
 }
 
+mod instanced_vertex
+{
+    vulkano_shaders::shader!
+    {
+        ty: "vertex",
+        path: "shaders/instanced.vert"
+    }
+}
+
 mod default_fragment
 {
     vulkano_shaders::shader!
@@ -119,6 +189,24 @@ mod default_fragment
     }
 }
 
+mod sdf_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/sdf.frag"
+    }
+}
+
+mod post_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/post.frag"
+    }
+}
+
 #[derive(BufferContents, Vertex, Debug, Clone, Copy)]
 #[repr(C)]
 struct SimpleVertex
@@ -139,18 +227,116 @@ where
 
     fn mouse_move(&mut self, _position: (f64, f64)) {}
 
+    // raw relative mouse movement in device units, unbounded and keeps coming
+    // when the cursor is locked (unlike mouse_move)
+    fn mouse_delta(&mut self, _delta: (f64, f64)) {}
+
+    // only called when the app has a fixed timestep, can run multiple times per frame
+    fn fixed_update(&mut self, _dt: f32) {}
+
     fn update(&mut self, _info: UpdateBuffersPartialInfo, _dt: f32) {}
 
+    // DrawInfo::next_subpass moves to the next subpass in multi subpass render passes
     fn draw(&mut self, _info: DrawInfo) {}
 
+    // called before the main render pass, OffscreenTarget::begin and end go in here
+    fn draw_offscreen(&mut self, _info: DrawInfo) {}
+
     fn resize(&mut self, _aspect: f32) {}
 
+    // called every time the swapchain gets recreated with the new framebuffer size in pixels
+    fn framebuffer_resized(&mut self, _size: [u32; 2]) {}
+
     fn swap_pipelines(&mut self, _resource_uploader: &ResourceUploader) {}
+
+    fn frame_captured(&mut self, _image: RgbaImage) {}
+
+    // the gpu device got lost (driver reset or similar) and everything got recreated on a new one,
+    // assets r reloaded but the objects the app made still point at the old device, remake them here
+    fn on_device_lost(&mut self, _info: InitPartialInfo) {}
+}
+
+// returning none falls back to the first srgb format
+pub type SurfaceFormatChooser = Box<dyn Fn(&[(Format, ColorSpace)]) -> Option<(Format, ColorSpace)>>;
+
+pub type DeviceFilter = Box<dyn Fn(&PhysicalDevice) -> bool>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMode
+{
+    Normal,
+    // cant leave the window
+    Confined,
+    // stays in place, use YanyaApp::mouse_delta for movement
+    Locked
+}
+
+impl CursorMode
+{
+    pub(crate) fn into_winit(self) -> CursorGrabMode
+    {
+        match self
+        {
+            Self::Normal => CursorGrabMode::None,
+            Self::Confined => CursorGrabMode::Confined,
+            Self::Locked => CursorGrabMode::Locked
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode
+{
+    Windowed,
+    Borderless,
+    // uses the biggest video mode of the monitor, borderless if it has none
+    Exclusive
+}
+
+impl FullscreenMode
+{
+    pub(crate) fn into_winit(self, monitor: Option<MonitorHandle>) -> Option<Fullscreen>
+    {
+        match self
+        {
+            Self::Windowed => None,
+            Self::Borderless => Some(Fullscreen::Borderless(monitor)),
+            Self::Exclusive =>
+            {
+                let mode = monitor.as_ref().and_then(|monitor|
+                {
+                    monitor.video_modes().max_by_key(|mode|
+                    {
+                        let size = mode.size();
+
+                        (size.width * size.height, mode.bit_depth(), mode.refresh_rate_millihertz())
+                    })
+                });
+
+                Some(mode.map(Fullscreen::Exclusive).unwrap_or_else(||
+                {
+                    eprintln!("no video modes available, using borderless fullscreen");
+
+                    Fullscreen::Borderless(monitor)
+                }))
+            }
+        }
+    }
 }
 
 pub struct AppOptions
 {
     assets_paths: AssetsPaths,
+    background_textures: bool,
+    background_models: bool,
+    image_count: Option<u32>,
+    texture_location: UniformLocation,
+    subpass_contents: SubpassContents,
+    fixed_timestep: Option<f32>,
+    max_delta: Option<f32>,
+    surface_format: Option<SurfaceFormatChooser>,
+    device_filter: Option<DeviceFilter>,
+    fonts: Option<FontsContainer>,
     shaders_query: Option<ShadersQuery>
 }
 
@@ -160,6 +346,16 @@ impl Default for AppOptions
     {
         Self{
             assets_paths: AssetsPaths::default(),
+            background_textures: false,
+            background_models: false,
+            image_count: None,
+            texture_location: UniformLocation{set: 0, binding: 0},
+            subpass_contents: SubpassContents::Inline,
+            fixed_timestep: None,
+            max_delta: None,
+            surface_format: None,
+            device_filter: None,
+            fonts: None,
             shaders_query: None
         }
     }
@@ -172,12 +368,13 @@ pub struct AssetsPaths
     models: Option<PathBuf>
 }
 
-type WrapperShaderFn = Box<dyn FnOnce(Arc<Device>) -> EntryPoint>;
+// shaders get loaded again for every device the app ends up on
+type WrapperShaderFn = Box<dyn Fn(Arc<Device>) -> EntryPoint>;
 
 pub trait ShaderWrappable
 {
     fn entry_point(
-        self,
+        &self,
         name: &str,
         device: Arc<Device>
     ) -> Option<EntryPoint>;
@@ -208,10 +405,10 @@ impl<T, E, F> ShaderWrappable for F
 where
     E: Display,
     T: EntryPointable,
-    F: FnOnce(Arc<Device>) -> Result<T, E>
+    F: Fn(Arc<Device>) -> Result<T, E>
 {
     fn entry_point(
-        self,
+        &self,
         name: &str,
         device: Arc<Device>
     ) -> Option<EntryPoint>
@@ -248,12 +445,12 @@ impl ShadersGroup<WrapperShaderFn>
     ) -> Self
     {
         Self{
-            vertex: Box::new(|device| vertex.entry_point("main", device).unwrap()),
-            fragment: Box::new(|device| fragment.entry_point("main", device).unwrap())
+            vertex: Box::new(move |device| vertex.entry_point("main", device).unwrap()),
+            fragment: Box::new(move |device| fragment.entry_point("main", device).unwrap())
         }
     }
 
-    pub fn load(self, device: Arc<Device>) -> ShadersGroup<EntryPoint>
+    pub fn load(&self, device: Arc<Device>) -> ShadersGroup<EntryPoint>
     {
         ShadersGroup{
             vertex: (self.vertex)(device.clone()),
@@ -277,8 +474,15 @@ pub struct Shader
 {
     pub shader: ShadersGroup<WrapperShaderFn>,
     pub per_vertex: Option<VertexBufferDescription>,
+    // bound as the second vertex buffer
+    pub per_instance: Option<VertexBufferDescription>,
     pub depth: Option<DepthState>,
-    pub stencil: Option<StencilState>
+    pub stencil: Option<StencilState>,
+    // none writes the colors as they are
+    pub blend: Option<AttachmentBlend>,
+    pub topology: PrimitiveTopology,
+    // index of the render pass subpass this pipeline draws in
+    pub subpass: u32
 }
 
 impl Default for Shader
@@ -291,8 +495,91 @@ impl Default for Shader
                 default_fragment::load
             ),
             per_vertex: None,
+            per_instance: None,
+            depth: None,
+            stencil: None,
+            blend: Some(AttachmentBlend::alpha()),
+            topology: PrimitiveTopology::TriangleList,
+            subpass: 0
+        }
+    }
+}
+
+impl Shader
+{
+    // objects dont have index buffers so their vertices are used in order,
+    // a line list model is just pairs of points
+    pub fn with_topology(mut self, topology: PrimitiveTopology) -> Self
+    {
+        self.topology = topology;
+
+        self
+    }
+
+    pub fn with_subpass(mut self, subpass: u32) -> Self
+    {
+        self.subpass = subpass;
+
+        self
+    }
+
+    // for render passes without a depth attachment, draws over whatever was there
+    // without blending so its cheaper
+    pub fn flat() -> Self
+    {
+        Self{
+            per_vertex: Some(Object::per_vertex()),
+            depth: None,
+            stencil: None,
+            blend: None,
+            ..Default::default()
+        }
+    }
+
+    pub fn sdf() -> Self
+    {
+        Self{
+            shader: ShadersGroup::new(
+                default_vertex::load,
+                sdf_fragment::load
+            ),
+            per_vertex: Some(SdfObject::per_vertex()),
+            per_instance: None,
             depth: None,
-            stencil: None
+            stencil: None,
+            blend: Some(AttachmentBlend::alpha()),
+            topology: PrimitiveTopology::TriangleList,
+            subpass: 0
+        }
+    }
+
+    // for drawing InstancedObjects
+    pub fn instanced() -> Self
+    {
+        Self{
+            shader: ShadersGroup::new(
+                instanced_vertex::load,
+                default_fragment::load
+            ),
+            per_vertex: Some(InstancedObject::per_vertex()),
+            per_instance: Some(InstancedObject::per_instance()),
+            ..Default::default()
+        }
+    }
+
+    // copies the scene subpass to the output in Rendering::new_post_process,
+    // draw a fullscreen quad with it after DrawInfo::next_subpass and
+    // bind the scene with DrawInfo::bind_input_attachment(location, 0)
+    pub fn post_process() -> Self
+    {
+        Self{
+            shader: ShadersGroup::new(
+                default_vertex::load,
+                post_fragment::load
+            ),
+            per_vertex: Some(Object::per_vertex()),
+            subpass: 1,
+            ..Default::default()
         }
     }
 }
@@ -349,17 +636,71 @@ impl ShadersContainer
     {
         self.shaders.is_empty()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Shader>
+    {
+        self.shaders.iter()
+    }
+
+    pub fn validate(&self) -> Result<(), AppError>
+    {
+        match self.shaders.iter().position(|shader| shader.per_vertex.is_none())
+        {
+            Some(index) => Err(AppError::MissingPerVertex(ShaderId(index))),
+            None => Ok(())
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AppError
+{
+    MissingPerVertex(ShaderId),
+    // the loader is missing or it cant make an instance
+    NoVulkan(String),
+    NoDevice(String),
+    SurfaceCreation(String),
+    // the device got lost and so did the one recreated after it
+    DeviceLost,
+    // a builtin rendering option was used after with_rendering
+    CustomRendering
 }
 
+impl Display for AppError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::MissingPerVertex(id) =>
+            {
+                write!(f, "per_vertex must be provided for shader #{}", id.get_raw())
+            },
+            Self::NoVulkan(err) => write!(f, "vulkan isnt available: {err}"),
+            Self::NoDevice(err) => write!(f, "no device usable for rendering: {err}"),
+            Self::SurfaceCreation(err) => write!(f, "couldnt create a window surface: {err}"),
+            Self::DeviceLost => write!(f, "the device was lost and couldnt be recreated"),
+            Self::CustomRendering => write!(f, "builtin rendering options cant change a custom rendering")
+        }
+    }
+}
+
+impl error::Error for AppError {}
+
 pub struct AppBuilder<UserApp: YanyaApp, T>
 {
     instance: Arc<Instance>,
+    debug_messenger: Option<DebugUtilsMessenger>,
     window_builder: WindowBuilder,
-    event_loop: EventLoop<()>,
+    // none when theres no display, only headless rendering works then
+    event_loop: Option<EventLoop<()>>,
     shaders: ShadersContainer,
     options: AppOptions,
     app_init: Option<UserApp::AppInfo>,
     rendering: Rendering<T>,
+    // none when the rendering is a custom one
+    rendering_config: Option<RenderingConfig>,
+    rendering_error: Option<AppError>,
     _user_app: PhantomData<UserApp>
 }
 
@@ -385,6 +726,59 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
         self
     }
 
+    pub fn with_min_size(mut self, size: [u32; 2]) -> Self
+    {
+        self.window_builder = self.window_builder.with_min_inner_size(PhysicalSize::<u32>::from(size));
+
+        self
+    }
+
+    pub fn with_max_size(mut self, size: [u32; 2]) -> Self
+    {
+        self.window_builder = self.window_builder.with_max_inner_size(PhysicalSize::<u32>::from(size));
+
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self
+    {
+        self.window_builder = self.window_builder.with_resizable(resizable);
+
+        self
+    }
+
+    pub fn with_fullscreen(mut self, mode: FullscreenMode) -> Self
+    {
+        let monitor = self.event_loop.as_ref().and_then(|event_loop| event_loop.primary_monitor());
+        let fullscreen = mode.into_winit(monitor);
+        self.window_builder = self.window_builder.with_fullscreen(fullscreen);
+
+        self
+    }
+
+    // recreates the instance, so call this before anything else
+    pub fn with_validation(mut self, state: bool) -> Self
+    {
+        let library = self.instance.library().clone();
+
+        match App::<UserApp>::create_instance(library, self.event_loop.as_ref(), state)
+        {
+            Ok(instance) =>
+            {
+                self.debug_messenger = None;
+                self.instance = instance;
+
+                if state
+                {
+                    self.debug_messenger = App::<UserApp>::create_debug_messenger(self.instance.clone());
+                }
+            },
+            Err(err) => eprintln!("{err}, continuing with the current instance")
+        }
+
+        self
+    }
+
     pub fn with_app_init(mut self, app_init: UserApp::AppInfo) -> Self
     {
         self.app_init = Some(app_init);
@@ -394,7 +788,17 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
 
     pub fn with_clear_color(mut self, color: [f32; 3]) -> Self
     {
-        self.rendering.clear[0] = Some([color[0], color[1], color[2], 1.0].into());
+        let color: ClearValue = [color[0], color[1], color[2], 1.0].into();
+
+        if let Some(config) = self.rendering_config.as_mut()
+        {
+            config.clear_color = color;
+        }
+
+        if let Some(clear) = self.rendering.clear.first_mut()
+        {
+            *clear = Some(color);
+        }
 
         self
     }
@@ -403,12 +807,15 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
     {
         AppBuilder{
             instance: self.instance,
+            debug_messenger: self.debug_messenger,
             window_builder: self.window_builder,
             event_loop: self.event_loop,
             shaders: self.shaders,
             options: self.options,
             app_init: self.app_init,
             rendering,
+            rendering_config: None,
+            rendering_error: self.rendering_error,
             _user_app: PhantomData
         }
     }
@@ -427,6 +834,100 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
         self
     }
 
+    // the main render pass begins for DrawInfo::execute_commands, objects can only be drawn
+    // after switching to an inline subpass with DrawInfo::next_subpass_with
+    pub fn with_secondary_command_buffers(mut self, state: bool) -> Self
+    {
+        self.options.subpass_contents = if state
+        {
+            SubpassContents::SecondaryCommandBuffers
+        } else
+        {
+            SubpassContents::Inline
+        };
+
+        self
+    }
+
+    pub fn with_texture_location(mut self, location: UniformLocation) -> Self
+    {
+        self.options.texture_location = location;
+
+        self
+    }
+
+    pub fn with_fixed_timestep(mut self, dt: f32) -> Self
+    {
+        assert!(dt > 0.0 && dt.is_finite(), "fixed timestep must be positive and finite, got {dt}");
+
+        self.options.fixed_timestep = Some(dt);
+
+        self
+    }
+
+    // clamps the dt passed to update (and fed into the fixed timestep) after long stalls
+    pub fn with_max_delta(mut self, dt: f32) -> Self
+    {
+        self.options.max_delta = Some(dt);
+
+        self
+    }
+
+    pub fn with_image_count(mut self, count: u32) -> Self
+    {
+        self.options.image_count = Some(count);
+
+        self
+    }
+
+    pub fn with_surface_format<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[(Format, ColorSpace)]) -> Option<(Format, ColorSpace)> + 'static
+    {
+        self.options.surface_format = Some(Box::new(f));
+
+        self
+    }
+
+    // if no device passes the filter it picks one like it normally would
+    pub fn with_device_filter(mut self, filter: DeviceFilter) -> Self
+    {
+        self.options.device_filter = Some(filter);
+
+        self
+    }
+
+    pub fn with_preferred_device_name(self, name: String) -> Self
+    {
+        self.with_device_filter(Box::new(move |device|
+        {
+            device.properties().device_name == name
+        }))
+    }
+
+    // without this the default system fonts get loaded
+    pub fn with_fonts(mut self, fonts: FontsContainer) -> Self
+    {
+        self.options.fonts = Some(fonts);
+
+        self
+    }
+
+    // textures r transparent until loaded, Assets::loading_remaining can drive a loading screen
+    pub fn with_background_textures(mut self, state: bool) -> Self
+    {
+        self.options.background_textures = state;
+
+        self
+    }
+
+    pub fn with_background_models(mut self, state: bool) -> Self
+    {
+        self.options.background_models = state;
+
+        self
+    }
+
     pub fn with_shaders(
         mut self,
         shaders: ShadersContainer,
@@ -439,27 +940,43 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
         self
     }
 
-    pub fn run(mut self)
-    where
-        T: Clone
+    fn prepare_shaders(&mut self) -> Result<(), AppError>
     {
+        if let Some(err) = self.rendering_error.take()
+        {
+            return Err(err);
+        }
+
         if self.shaders.is_empty()
         {
             // load default shaders
-            let id = self.shaders.push(Shader::default());
+            let id = self.shaders.push(Shader{
+                per_vertex: Some(Object::per_vertex()),
+                ..Default::default()
+            });
 
             self.options.shaders_query = Some(Box::new(move |_| id));
         }
 
-        let window = Arc::new(self.window_builder.build(&self.event_loop).unwrap());
-
-        let surface = Surface::from_window(self.instance.clone(), window)
-            .unwrap();
+        self.shaders.validate()
+    }
 
-        let (physical_device, (device, queues)) =
-            Self::create_device(surface.clone(), self.instance);
+    fn create_graphics_info(
+        instance: &Arc<Instance>,
+        shaders: &ShadersContainer,
+        rendering: Rc<Rendering<T>>,
+        device_filter: Option<&DeviceFilter>,
+        image_count: Option<u32>,
+        surface: Option<Arc<Surface>>
+    ) -> Result<GraphicsInfo<T>, AppError>
+    {
+        let (physical_device, device, queues) = Self::create_device(
+            surface.clone(),
+            instance.clone(),
+            device_filter
+        )?;
 
-        let pipeline_infos = self.shaders.into_iter().enumerate().map(|(index, shader_item)|
+        let pipeline_infos = shaders.iter().map(|shader_item|
         {
             let shader = shader_item.shader.load(device.clone());
 
@@ -471,46 +988,142 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
 
             let layout = PipelineLayout::new(device.clone(), info).unwrap();
 
-            let per_vertex = shader_item.per_vertex.unwrap_or_else(||
-            {
-                panic!("per_vertex must be provided for shader #{index}")
-            });
+            // checked in validate
+            let per_vertex = shader_item.per_vertex.clone().unwrap();
 
             PipelineCreateInfo{
                 stages: stages.into(),
                 shaders: shader,
                 per_vertex,
+                per_instance: shader_item.per_instance.clone(),
                 layout,
                 depth: shader_item.depth,
-                stencil: shader_item.stencil
+                stencil: shader_item.stencil.clone(),
+                blend: shader_item.blend,
+                topology: shader_item.topology,
+                subpass: shader_item.subpass
             }
         }).collect();
 
-        let graphics_info = GraphicsInfo{
+        Ok(GraphicsInfo{
             surface,
             physical_device,
             device,
             pipeline_infos,
-            queues: queues.collect(),
-            rendering: self.rendering
-        };
+            queues,
+            image_count,
+            rendering
+        })
+    }
+
+    // keeps everything needed to make the device again after its lost
+    fn graphics_creator(
+        instance: Arc<Instance>,
+        shaders: ShadersContainer,
+        rendering: Rendering<T>,
+        options: &mut AppOptions
+    ) -> GraphicsCreator<T>
+    where
+        T: 'static
+    {
+        let rendering = Rc::new(rendering);
+        let device_filter = options.device_filter.take();
+        let image_count = options.image_count;
+
+        Box::new(move |surface|
+        {
+            Self::create_graphics_info(
+                &instance,
+                &shaders,
+                rendering.clone(),
+                device_filter.as_ref(),
+                image_count,
+                surface
+            )
+        })
+    }
+
+    pub fn run(mut self) -> Result<(), AppError>
+    where
+        T: Clone + 'static
+    {
+        self.prepare_shaders()?;
+
+        let event_loop = self.event_loop.ok_or_else(||
+        {
+            AppError::SurfaceCreation("no display to open a window on".to_owned())
+        })?;
+
+        let window = self.window_builder.build(&event_loop)
+            .map_err(|err| AppError::SurfaceCreation(err.to_string()))?;
+
+        let window = Arc::new(window);
+        window.set_ime_allowed(true);
+
+        let surface = Surface::from_window(self.instance.clone(), window)
+            .map_err(|err| AppError::SurfaceCreation(err.to_string()))?;
+
+        let graphics_creator = Self::graphics_creator(
+            self.instance,
+            self.shaders,
+            self.rendering,
+            &mut self.options
+        );
+
+        let graphics_info = graphics_creator(Some(surface))?;
+
+        // has to live until the app is done
+        let _debug_messenger = self.debug_messenger.take();
 
         window::run::<UserApp, T>(
+            graphics_creator,
             graphics_info,
-            self.event_loop,
+            event_loop,
             self.options,
             self.app_init.unwrap_or_default()
         );
+
+        Ok(())
+    }
+
+    // runs the app without a window or swapchain and returns every rendered frame,
+    // only the presentation is different so draw works the same as normally
+    pub fn run_headless(mut self, extent: [u32; 2], frames: usize) -> Result<Vec<RgbaImage>, AppError>
+    where
+        T: Clone + 'static
+    {
+        self.prepare_shaders()?;
+
+        let graphics_creator = Self::graphics_creator(
+            self.instance,
+            self.shaders,
+            self.rendering,
+            &mut self.options
+        );
+
+        let graphics_info = graphics_creator(None)?;
+
+        let _debug_messenger = self.debug_messenger.take();
+
+        window::run_headless::<UserApp, T>(
+            graphics_creator,
+            graphics_info,
+            self.options,
+            self.app_init.unwrap_or_default(),
+            extent,
+            frames
+        )
     }
 
     fn get_physical(
-        surface: Arc<Surface>,
+        surface: Option<Arc<Surface>>,
         instance: Arc<Instance>,
-        device_extensions: &DeviceExtensions
-    ) -> (Arc<PhysicalDevice>, u32)
+        device_extensions: &DeviceExtensions,
+        filter: Option<&DeviceFilter>
+    ) -> Result<(Arc<PhysicalDevice>, u32), AppError>
     {
-        instance.enumerate_physical_devices()
-            .expect("no devices that support vulkan found :(")
+        let devices: Vec<_> = instance.enumerate_physical_devices()
+            .map_err(|err| AppError::NoDevice(err.to_string()))?
             .filter(|device| device.supported_extensions().contains(device_extensions))
             .filter_map(|device|
             {
@@ -519,48 +1132,148 @@ impl<UserApp: YanyaApp + 'static, T> AppBuilder<UserApp, T>
                     .enumerate()
                     .position(|(index, queue)|
                     {
-                        queue.queue_flags.contains(QueueFlags::GRAPHICS)
-                            && device.surface_support(index as u32, &surface).unwrap_or(false)
+                        let supports_surface = surface.as_ref().map(|surface|
+                        {
+                            device.surface_support(index as u32, surface).unwrap_or(false)
+                        }).unwrap_or(true);
+
+                        queue.queue_flags.contains(QueueFlags::GRAPHICS) && supports_surface
                     })
                     .map(|index| (device, index as u32))
-            }).min_by_key(|(device, _)|
+            }).collect();
+
+        let rank = |(device, _): &&(Arc<PhysicalDevice>, u32)|
+        {
+            match device.properties().device_type
             {
-                match device.properties().device_type
-                {
-                    PhysicalDeviceType::DiscreteGpu => 0,
-                    PhysicalDeviceType::IntegratedGpu => 1,
-                    PhysicalDeviceType::VirtualGpu => 2,
-                    PhysicalDeviceType::Cpu => 3,
-                    _ => 4
-                }
-            }).expect("no viable device for rendering :(")
+                PhysicalDeviceType::DiscreteGpu => 0,
+                PhysicalDeviceType::IntegratedGpu => 1,
+                PhysicalDeviceType::VirtualGpu => 2,
+                PhysicalDeviceType::Cpu => 3,
+                _ => 4
+            }
+        };
+
+        let preferred = filter.and_then(|filter|
+        {
+            let found = devices.iter().filter(|(device, _)| filter(device)).min_by_key(rank);
+
+            if found.is_none()
+            {
+                eprintln!("no device matches the filter, picking the default one");
+            }
+
+            found
+        });
+
+        preferred.or_else(|| devices.iter().min_by_key(rank))
+            .cloned()
+            .ok_or_else(|| AppError::NoDevice("none of the devices support rendering".to_owned()))
     }
 
     fn create_device(
-        surface: Arc<Surface>,
-        instance: Arc<Instance>
-    ) -> (Arc<PhysicalDevice>, (Arc<Device>, impl ExactSizeIterator<Item=Arc<Queue>>))
+        surface: Option<Arc<Surface>>,
+        instance: Arc<Instance>,
+        filter: Option<&DeviceFilter>
+    ) -> Result<(Arc<PhysicalDevice>, Arc<Device>, Queues), AppError>
     {
         let device_extensions = DeviceExtensions{
-            khr_swapchain: true,
+            khr_swapchain: surface.is_some(),
             ..DeviceExtensions::empty()
         };
 
         let (physical_device, queue_family_index) =
-            Self::get_physical(surface, instance, &device_extensions);
+            Self::get_physical(surface, instance, &device_extensions, filter)?;
 
         eprintln!("using {}", physical_device.properties().device_name);
 
-        (physical_device.clone(), Device::new(
-            physical_device,
-            DeviceCreateInfo{
-                queue_create_infos: vec![QueueCreateInfo{
+        let compute_family_index = physical_device.queue_family_properties()
+            .iter()
+            .position(|queue|
+            {
+                queue.queue_flags.contains(QueueFlags::COMPUTE)
+                    && !queue.queue_flags.contains(QueueFlags::GRAPHICS)
+            })
+            .map(|index| index as u32);
+
+        // a family that can only transfer is usually a separate dma engine
+        let transfer_family_index = physical_device.queue_family_properties()
+            .iter()
+            .position(|queue|
+            {
+                queue.queue_flags.contains(QueueFlags::TRANSFER)
+                    && !queue.queue_flags.intersects(QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+            })
+            .map(|index| index as u32);
+
+        let queue_create_infos = Some(queue_family_index).into_iter()
+            .chain(compute_family_index)
+            .chain(transfer_family_index)
+            .map(|queue_family_index|
+            {
+                QueueCreateInfo{
                     queue_family_index,
                     ..Default::default()
-                }],
+                }
+            }).collect();
+
+        let (device, mut queues) = Device::new(
+            physical_device.clone(),
+            DeviceCreateInfo{
+                queue_create_infos,
                 enabled_extensions: device_extensions,
                 ..Default::default()
-            }).expect("couldnt create device...."))
+            }).map_err(|err| AppError::NoDevice(err.to_string()))?;
+
+        let graphics = queues.next().unwrap();
+        let compute = compute_family_index.map(|_| queues.next().unwrap());
+        let transfer = transfer_family_index.map(|_| queues.next().unwrap());
+
+        Ok((physical_device, device, Queues{graphics, compute, transfer}))
+    }
+}
+
+impl<UserApp: YanyaApp + 'static> AppBuilder<UserApp, ()>
+{
+    // changes only its part of the builtin rendering, with a custom rendering
+    // the error shows up when running the app
+    fn with_rendering_config(mut self, f: impl FnOnce(&mut RenderingConfig)) -> Self
+    {
+        if let Some(config) = self.rendering_config.as_mut()
+        {
+            f(config);
+
+            self.rendering = config.clone().into();
+        } else
+        {
+            self.rendering_error.get_or_insert(AppError::CustomRendering);
+        }
+
+        self
+    }
+
+    // none leaves the depth attachment uncleared
+    pub fn with_depth_clear(self, depth_clear: Option<f32>) -> Self
+    {
+        self.with_rendering_config(|config| config.depth_clear = depth_clear)
+    }
+
+    // extra color attachments written by fragment shader outputs at locations 1 and up
+    pub fn with_render_targets(self, targets: Vec<(Format, ClearValue)>) -> Self
+    {
+        self.with_rendering_config(|config| config.targets = targets)
+    }
+
+    // gets clamped down to what the device supports
+    pub fn with_msaa(self, samples: SampleCount) -> Self
+    {
+        self.with_rendering_config(|config| config.samples = samples)
+    }
+
+    // no depth attachment, the shaders should be made with Shader::flat
+    pub fn with_flat_rendering(self) -> Self
+    {
+        self.with_rendering_config(|config| config.depth = false)
     }
 }
 
@@ -574,29 +1287,106 @@ impl<UserApp: YanyaApp + 'static> App<UserApp>
     #[allow(clippy::new_ret_no_self)]
     pub fn new() -> AppBuilder<UserApp, ()>
     {
-        let library = VulkanLibrary::new().expect("nyo vulkan? ;-;");
+        Self::try_new().unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let event_loop = EventLoop::new().unwrap();
-        event_loop.listen_device_events(DeviceEvents::WhenFocused);
+    // for showing a proper error instead of crashing when vulkan isnt there
+    pub fn try_new() -> Result<AppBuilder<UserApp, ()>, AppError>
+    {
+        let library = VulkanLibrary::new().map_err(|err| AppError::NoVulkan(err.to_string()))?;
 
-        let enabled_extensions = Surface::required_extensions(&event_loop);
-        let instance = Instance::new(
-            library,
-            InstanceCreateInfo{
-                enabled_extensions,
-                ..Default::default()
-            }
-        ).expect("cant create vulkan instance..");
+        let event_loop = EventLoop::new().map_err(|err|
+        {
+            eprintln!("cant create an event loop, only headless rendering is available ({err})");
+        }).ok();
 
-        AppBuilder{
+        if let Some(event_loop) = event_loop.as_ref()
+        {
+            event_loop.listen_device_events(DeviceEvents::WhenFocused);
+        }
+
+        let instance = Self::create_instance(library, event_loop.as_ref(), false)?;
+
+        Ok(AppBuilder{
             instance,
+            debug_messenger: None,
             window_builder: WindowBuilder::new(),
             event_loop,
             shaders: ShadersContainer::new(),
             options: AppOptions::default(),
             app_init: None,
             rendering: Rendering::new_default([0.0, 0.0, 0.0, 1.0].into()),
+            rendering_config: Some(RenderingConfig::new([0.0, 0.0, 0.0, 1.0].into())),
+            rendering_error: None,
             _user_app: PhantomData
+        })
+    }
+
+    fn create_instance(
+        library: Arc<VulkanLibrary>,
+        event_loop: Option<&EventLoop<()>>,
+        validation: bool
+    ) -> Result<Arc<Instance>, AppError>
+    {
+        let mut enabled_extensions = event_loop.map(Surface::required_extensions)
+            .unwrap_or_else(InstanceExtensions::empty);
+        let mut enabled_layers = Vec::new();
+
+        if validation
+        {
+            let has_layer = library.layer_properties().map(|mut layers|
+            {
+                layers.any(|layer| layer.name() == VALIDATION_LAYER)
+            }).unwrap_or(false);
+
+            if has_layer
+            {
+                enabled_layers.push(VALIDATION_LAYER.to_owned());
+                enabled_extensions.ext_debug_utils = true;
+            } else
+            {
+                eprintln!("validation layer isnt installed, continuing without it");
+            }
         }
+
+        Instance::new(
+            library,
+            InstanceCreateInfo{
+                enabled_extensions,
+                enabled_layers,
+                ..Default::default()
+            }
+        ).map_err(|err| AppError::NoVulkan(err.to_string()))
+    }
+
+    fn create_debug_messenger(instance: Arc<Instance>) -> Option<DebugUtilsMessenger>
+    {
+        if !instance.enabled_extensions().ext_debug_utils
+        {
+            return None;
+        }
+
+        // doesnt call into vulkan so its fine
+        let callback = unsafe{
+            DebugUtilsMessengerCallback::new(|severity, _kind, data|
+            {
+                eprintln!("vulkan {severity:?}: {}", data.message);
+            })
+        };
+
+        DebugUtilsMessenger::new(
+            instance,
+            DebugUtilsMessengerCreateInfo{
+                message_severity: DebugUtilsMessageSeverity::ERROR
+                    | DebugUtilsMessageSeverity::WARNING,
+                message_type: DebugUtilsMessageType::GENERAL
+                    | DebugUtilsMessageType::VALIDATION
+                    | DebugUtilsMessageType::PERFORMANCE,
+                ..DebugUtilsMessengerCreateInfo::user_callback(callback)
+            }
+        ).map_err(|err|
+        {
+            eprintln!("couldnt create debug messenger: {err}");
+        }).ok()
     }
 }
@@ -1,6 +1,7 @@
 #[allow(unused_imports)]
 use std::{
     fmt,
+    mem,
     cell::RefCell,
     sync::Arc
 };
@@ -11,14 +12,19 @@ use vulkano::{
     buffer::{BufferContents, Subbuffer},
     pipeline::{
         PipelineBindPoint,
-        graphics::vertex_input::{VertexBufferDescription, Vertex}
+        graphics::{
+            vertex_input::{VertexBufferDescription, Vertex},
+            viewport::Scissor
+        }
     }
 };
 
 use nalgebra::{Vector3, Vector4, Matrix4};
 
 use crate::{
-    allocators::ObjectAllocator,
+    allocators::{ObjectAllocator, FrameSubbuffers, Recycler},
+    sprite_batch::Sprite,
+    occluding_plane::{OccluderPoints, ShadowCaster},
     transform::{Transform, OnTransformCallback, TransformContainer}
 };
 
@@ -28,19 +34,20 @@ pub use object_transform::ObjectTransform;
 
 use game_object::*;
 pub use model::Model;
-pub use texture::Texture;
+pub use texture::{Texture, MipMode};
 
 mod object_transform;
 
 pub mod game_object;
+pub mod object_allocator;
 pub mod resource_uploader;
 pub mod model;
 pub mod texture;
 
 
-pub trait NormalGraphicalObject<T: BufferContents>
+pub trait NormalGraphicalObject<T: BufferContents + Copy>
 {
-    fn subbuffer(&self) -> Subbuffer<[T]>;
+    fn subbuffer(&self, frame_parity: bool) -> Subbuffer<[T]>;
     fn vertices(&self, projection_view: Matrix4<f32>) -> Box<[T]>;
 
     fn set_updated(&mut self, object_info: &ObjectCreatePartialInfo);
@@ -56,11 +63,8 @@ pub trait NormalGraphicalObject<T: BufferContents>
 
         self.set_updated(&info.partial);
 
-        info.partial.builder_wrapper.builder()
-            .update_buffer(
-                self.subbuffer(),
-                vertices
-            ).unwrap();
+        let subbuffer = self.subbuffer(info.partial.frame_parity);
+        info.partial.builder_wrapper.update_buffer(&subbuffer, &vertices);
     }
 }
 
@@ -94,9 +98,9 @@ macro_rules! impl_updated_check
 
 impl NormalGraphicalObject<ObjectVertex> for Object
 {
-    fn subbuffer(&self) -> Subbuffer<[ObjectVertex]>
+    fn subbuffer(&self, frame_parity: bool) -> Subbuffer<[ObjectVertex]>
     {
-        self.subbuffer.clone()
+        self.subbuffers[frame_parity as usize].clone()
     }
 
     fn vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
@@ -109,7 +113,7 @@ impl NormalGraphicalObject<ObjectVertex> for Object
 
 #[derive(BufferContents, Vertex, Clone, Copy)]
 #[repr(C)]
-struct ObjectVertex
+pub(crate) struct ObjectVertex
 {
     #[format(R32G32B32A32_SFLOAT)]
     pub position: [f32; 4],
@@ -123,7 +127,11 @@ pub struct Object
     model: Arc<RwLock<Model>>,
     texture: Arc<RwLock<Texture>>,
     transform: ObjectTransform,
-    subbuffer: Subbuffer<[ObjectVertex]>,
+    layer: Option<u8>,
+    clip: Option<Scissor>,
+    subbuffers: FrameSubbuffers<ObjectVertex>,
+    // none for transient objects, the allocator takes those back every frame by itself
+    recycler: Option<Recycler>,
     #[cfg(debug_assertions)]
     updated_buffers: bool
 }
@@ -149,13 +157,42 @@ impl Object
         allocator: &ObjectAllocator
     ) -> Self
     {
-        let subbuffer = allocator.subbuffer(model.read().vertices.len() as u64);
+        let subbuffers = allocator.frame_subbuffers(model.read().vertices.len() as u64);
+
+        Self::from_subbuffers(model, texture, transform, subbuffers, Some(allocator.recycler()))
+    }
+
+    // only lives for the current frame, the buffer gets reused by the next one
+    pub fn new_transient(
+        model: Arc<RwLock<Model>>,
+        texture: Arc<RwLock<Texture>>,
+        transform: ObjectTransform,
+        allocator: &ObjectAllocator
+    ) -> Self
+    {
+        // it doesnt live to see the other frame parity so both can share a buffer
+        let subbuffer = allocator.transient(model.read().vertices.len() as u64);
+        let subbuffers = [subbuffer.clone(), subbuffer];
+
+        Self::from_subbuffers(model, texture, transform, subbuffers, None)
+    }
 
+    fn from_subbuffers(
+        model: Arc<RwLock<Model>>,
+        texture: Arc<RwLock<Texture>>,
+        transform: ObjectTransform,
+        subbuffers: FrameSubbuffers<ObjectVertex>,
+        recycler: Option<Recycler>
+    ) -> Self
+    {
         Self{
             model,
             texture,
             transform,
-            subbuffer,
+            layer: None,
+            clip: None,
+            subbuffers,
+            recycler,
             #[cfg(debug_assertions)]
             updated_buffers: false
         }
@@ -163,18 +200,90 @@ impl Object
 
     fn calculate_vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
     {
-        let transform = self.transform.matrix();
-
         let model = self.model.read();
 
+        Self::transformed_vertices(
+            &model,
+            self.transform.matrix(),
+            self.layer,
+            projection_view
+        ).collect::<Box<[_]>>()
+    }
+
+    // also used by the sprite batch for every object pushed into it
+    pub(crate) fn transformed_vertices(
+        model: &Model,
+        transform: Matrix4<f32>,
+        layer: Option<u8>,
+        projection_view: Matrix4<f32>
+    ) -> impl Iterator<Item=ObjectVertex> + '_
+    {
         model.vertices.iter().zip(model.uvs.iter()).map(move |(vertex, uv)|
         {
             let vertex = Vector4::new(vertex[0], vertex[1], vertex[2], 1.0);
 
             let vertex = projection_view * transform * vertex;
 
+            let vertex = if let Some(layer) = layer
+            {
+                Self::layered_vertex(layer, vertex)
+            } else
+            {
+                vertex
+            };
+
             ObjectVertex{position: vertex.into(), uv: *uv}
-        }).collect::<Box<[_]>>()
+        })
+    }
+
+    pub(crate) fn sprite(&self) -> Sprite
+    {
+        Sprite{
+            model: self.model.clone(),
+            texture: self.texture.clone(),
+            transform: self.transform.matrix(),
+            layer: self.layer
+        }
+    }
+
+    pub fn model_vertices(&self) -> Box<[Vector3<f32>]>
+    {
+        self.model.read().vertices.iter().map(|vertex| Vector3::from(*vertex)).collect()
+    }
+
+    pub fn world_vertices(&self) -> Box<[Vector3<f32>]>
+    {
+        let transform = self.transform.matrix();
+
+        self.model.read().vertices.iter().map(|vertex|
+        {
+            let vertex = Vector4::new(vertex[0], vertex[1], vertex[2], 1.0);
+
+            (transform * vertex).xyz()
+        }).collect()
+    }
+
+    // higher layers always end up closer than lower ones, depth inside a layer still works
+    fn layered_vertex(layer: u8, mut vertex: Vector4<f32>) -> Vector4<f32>
+    {
+        let layers_count = u8::MAX as f32 + 1.0;
+
+        let depth = (vertex.z / vertex.w).clamp(0.0, 1.0);
+        let start = (u8::MAX - layer) as f32;
+
+        vertex.z = ((start + depth) / layers_count) * vertex.w;
+
+        vertex
+    }
+
+    pub fn layer(&self) -> Option<u8>
+    {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: Option<u8>)
+    {
+        self.layer = layer;
     }
 
     pub fn set_origin(&mut self, origin: Vector3<f32>)
@@ -182,6 +291,11 @@ impl Object
         self.transform.set_origin(origin);
     }
 
+    pub fn set_pivot(&mut self, pivot: Vector3<f32>)
+    {
+        self.transform.set_pivot(pivot);
+    }
+
     pub fn set_inplace_model_same_sized(&mut self, model: Model)
     {
         let mut current_model = self.model.write();
@@ -190,6 +304,18 @@ impl Object
         *current_model = model;
     }
 
+    pub fn clip(&self) -> Option<Scissor>
+    {
+        self.clip
+    }
+
+    // intersected with the current scissor while drawing, after the object is drawn
+    // the scissor pops back to the parent clip (the whole screen if there isnt one)
+    pub fn set_clip(&mut self, clip: Option<Scissor>)
+    {
+        self.clip = clip;
+    }
+
     pub fn set_texture(&mut self, texture: Arc<RwLock<Texture>>)
     {
         self.texture = texture;
@@ -205,6 +331,30 @@ impl Object
         &self.texture
     }
 
+    // the model can be swapped for one with more vertices (like when its done loading)
+    fn fit_model(&mut self, allocator: &ObjectAllocator)
+    {
+        let size = self.model.read().vertices.len() as u64;
+        if size <= self.subbuffers[0].len()
+        {
+            return;
+        }
+
+        if self.recycler.is_some()
+        {
+            let subbuffers = allocator.frame_subbuffers(size);
+            mem::replace(&mut self.subbuffers, subbuffers).into_iter().for_each(|subbuffer|
+            {
+                allocator.recycle(subbuffer);
+            });
+        } else
+        {
+            // the old one gets recycled at the end of the frame already
+            let subbuffer = allocator.transient(size);
+            self.subbuffers = [subbuffer.clone(), subbuffer];
+        }
+    }
+
     fn needs_draw(&self) -> bool
     {
         !self.model.read().vertices.is_empty()
@@ -220,6 +370,9 @@ impl GameObject for Object
 {
     fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
     {
+        let object_factory = info.partial.object_factory.clone();
+        self.fit_model(object_factory.allocator());
+
         self.normal_update_buffers(info);
     }
 
@@ -234,19 +387,43 @@ impl GameObject for Object
 
         let size = self.model.read().vertices.len() as u32;
 
+        let (set, descriptor_set) = {
+            let texture = self.texture.read();
+
+            (texture.location().set, texture.descriptor_set())
+        };
+
+        if let Some(clip) = self.clip
+        {
+            info.push_scissor(clip);
+        }
+
         let layout = info.current_layout();
         info.object_info.builder_wrapper.builder()
             .bind_descriptor_sets(
                 PipelineBindPoint::Graphics,
                 layout,
-                0,
-                self.texture.read().descriptor_set()
+                set,
+                descriptor_set
             )
             .unwrap()
-            .bind_vertex_buffers(0, self.subbuffer.clone())
+            .bind_vertex_buffers(0, self.subbuffer(info.object_info.frame_parity))
             .unwrap()
             .draw(size, 1, 0, 0)
             .unwrap();
+
+        if self.clip.is_some()
+        {
+            info.pop_scissor();
+        }
+    }
+}
+
+impl ShadowCaster for Object
+{
+    fn shadow_points(&self, origin: Vector3<f32>) -> Option<OccluderPoints>
+    {
+        OccluderPoints::silhouette(&self.world_vertices(), origin)
     }
 }
 
@@ -271,6 +448,17 @@ impl TransformContainer for Object
     }
 }
 
+impl Drop for Object
+{
+    fn drop(&mut self)
+    {
+        if let Some(recycler) = self.recycler.as_ref()
+        {
+            self.subbuffers.iter().for_each(|subbuffer| recycler.recycle(subbuffer.clone()));
+        }
+    }
+}
+
 impl fmt::Debug for Object
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
@@ -279,6 +467,7 @@ impl fmt::Debug for Object
             .field("model", &self.model)
             .field("texture", &self.texture)
             .field("transform", &self.transform)
+            .field("layer", &self.layer)
             .finish()
     }
 }
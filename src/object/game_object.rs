@@ -1,20 +1,34 @@
 use std::{
     rc::Rc,
-    sync::Arc
+    cell::Cell,
+    sync::Arc,
+    ops::{Deref, DerefMut}
 };
 
-use nalgebra::Matrix4;
+use nalgebra::{Vector2, Matrix4};
+
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, window::Window};
 
 use parking_lot::Mutex;
 
 use vulkano::{
     pipeline::{PipelineBindPoint, PipelineLayout, graphics::viewport::Scissor},
-    descriptor_set::WriteDescriptorSet,
+    shader::ShaderStages,
+    render_pass::Framebuffer,
+    image::view::ImageView,
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
     buffer::{
         Subbuffer,
         BufferContents
     },
-    command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder,
+        PrimaryAutoCommandBuffer,
+        SecondaryAutoCommandBuffer,
+        SubpassBeginInfo,
+        SubpassEndInfo,
+        SubpassContents
+    },
 };
 
 use crate::{
@@ -23,7 +37,13 @@ use crate::{
     UniformLocation,
     ShaderId,
     PipelineInfo,
+    ComputeQueue,
+    FullscreenMode,
+    CursorMode,
+    FrameStats,
+    CaptureError,
     allocators::UniformAllocator,
+    render_target::{OffscreenCreator, OffscreenTarget},
     camera::Camera
 };
 
@@ -34,19 +54,211 @@ mod builder_wrapper;
 
 pub type CommandBuilderType = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
 
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo
+{
+    // also the amount of frames rendered before this one
+    pub index: u64,
+    // how many earlier frames the gpu can still be working on while this one is recorded
+    pub in_flight: usize,
+    // seconds since the app started, same for the whole frame
+    pub elapsed: f64
+}
+
+impl FrameInfo
+{
+    // how many copies of a resource written every frame are needed so the current frame
+    // never writes one the gpu might still be reading
+    pub fn ring_size(&self) -> usize
+    {
+        self.in_flight + 1
+    }
+
+    pub fn ring_index(&self) -> usize
+    {
+        (self.index % self.ring_size() as u64) as usize
+    }
+
+    // halton (2, 3) sequence in -0.5..0.5, repeats every 16 frames
+    pub fn subpixel_jitter(&self) -> Vector2<f32>
+    {
+        fn halton(mut index: u64, base: u64) -> f32
+        {
+            let mut fraction = 1.0;
+            let mut result = 0.0;
+
+            while index > 0
+            {
+                fraction /= base as f32;
+                result += fraction * (index % base) as f32;
+
+                index /= base;
+            }
+
+            result
+        }
+
+        let index = (self.index % 16) + 1;
+
+        Vector2::new(halton(index, 2), halton(index, 3)) - Vector2::repeat(0.5)
+    }
+}
+
 pub struct ObjectCreatePartialInfo<'a>
 {
     pub builder_wrapper: BuilderWrapper<'a>,
     pub assets: Arc<Mutex<Assets>>,
     pub object_factory: Rc<ObjectFactory>,
     pub uniform_allocator: Rc<UniformAllocator>,
+    pub compute: Rc<ComputeQueue>,
     pub size: [f32; 2],
-    #[cfg(debug_assertions)]
+    pub frame: FrameInfo,
+    pub frame_stats: &'a FrameStats,
+    // last cursor position in 0..1 screen coordinates
+    pub mouse_position: Vector2<f32>,
+    pub(crate) capture: Rc<Cell<bool>>,
+    pub(crate) capture_error: Option<CaptureError>,
+    pub(crate) clear_color: Rc<Cell<Option<[f32; 4]>>>,
+    pub(crate) offscreen: OffscreenCreator,
+    // none when rendering headless
+    pub(crate) window: Option<Arc<Window>>,
+    // flips every frame, picks which of the double buffered subbuffers gets used
     pub frame_parity: bool
 }
 
 impl<'a> ObjectCreatePartialInfo<'a>
 {
+    // all the window related functions do nothing when theres no window
+    pub fn with_window<T>(&self, f: impl FnOnce(&Window) -> T) -> Option<T>
+    {
+        self.window.as_deref().map(f)
+    }
+
+    // the rendered frame gets passed to YanyaApp::frame_captured after its done
+    pub fn request_capture(&self) -> Result<(), CaptureError>
+    {
+        if let Some(err) = self.capture_error
+        {
+            return Err(err);
+        }
+
+        self.capture.set(true);
+
+        Ok(())
+    }
+
+    // replaces the color attachment clear color from this frame onwards, depth isnt touched
+    pub fn set_clear_color(&self, color: [f32; 4])
+    {
+        self.clear_color.set(Some(color));
+    }
+
+    // reads the asset folders again, ids stay the same and objects using them see the changes
+    pub fn reload_assets(&mut self)
+    {
+        self.assets.lock().reload(self.builder_wrapper.resource_uploader());
+    }
+
+    // extent is in pixels, uses the same attachments as the main render pass
+    pub fn create_offscreen_target(
+        &mut self,
+        extent: [u32; 2],
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> OffscreenTarget
+    {
+        OffscreenTarget::new(
+            self.builder_wrapper.resource_uploader(),
+            &self.offscreen,
+            extent,
+            location,
+            shader
+        )
+    }
+
+    pub fn set_window_min_size(&self, size: Option<[u32; 2]>)
+    {
+        self.with_window(|window| window.set_min_inner_size(size.map(PhysicalSize::<u32>::from)));
+    }
+
+    pub fn set_window_max_size(&self, size: Option<[u32; 2]>)
+    {
+        self.with_window(|window| window.set_max_inner_size(size.map(PhysicalSize::<u32>::from)));
+    }
+
+    pub fn set_window_resizable(&self, resizable: bool)
+    {
+        self.with_window(|window| window.set_resizable(resizable));
+    }
+
+    // the swapchain gets recreated through the resize event like with any other resize
+    pub fn set_fullscreen(&self, mode: FullscreenMode)
+    {
+        self.with_window(|window| window.set_fullscreen(mode.into_winit(window.current_monitor())));
+    }
+
+    pub fn is_fullscreen(&self) -> bool
+    {
+        self.with_window(|window| window.fullscreen().is_some()).unwrap_or(false)
+    }
+
+    // switches between windowed and borderless
+    pub fn toggle_fullscreen(&self)
+    {
+        let mode = if self.is_fullscreen()
+        {
+            FullscreenMode::Windowed
+        } else
+        {
+            FullscreenMode::Borderless
+        };
+
+        self.set_fullscreen(mode);
+    }
+
+    pub fn set_cursor_mode(&self, mode: CursorMode)
+    {
+        let result = self.with_window(|window| window.set_cursor_grab(mode.into_winit()));
+
+        if let Some(Err(err)) = result
+        {
+            if mode == CursorMode::Locked
+            {
+                eprintln!("cant lock the cursor ({err}), confining instead");
+
+                self.set_cursor_mode(CursorMode::Confined);
+            } else
+            {
+                eprintln!("cant set cursor mode to {mode:?}: {err}");
+            }
+        }
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool)
+    {
+        self.with_window(|window| window.set_cursor_visible(visible));
+    }
+
+    pub fn set_ime_allowed(&self, allowed: bool)
+    {
+        self.with_window(|window| window.set_ime_allowed(allowed));
+    }
+
+    // where the ime candidate box goes, position and size are in 0..1 screen space
+    pub fn set_ime_area(&self, position: Vector2<f32>, size: Vector2<f32>)
+    {
+        let [width, height] = self.size;
+        let to_pixels = |value: Vector2<f32>| [value.x * width, value.y * height];
+
+        self.with_window(|window|
+        {
+            window.set_ime_cursor_area(
+                PhysicalPosition::<f32>::from(to_pixels(position)),
+                PhysicalSize::<f32>::from(to_pixels(size))
+            )
+        });
+    }
+
     pub fn aspect(&self) -> f32
     {
         let [x, y] = self.size;
@@ -54,6 +266,11 @@ impl<'a> ObjectCreatePartialInfo<'a>
         x / y
     }
 
+    pub fn mouse_world_position(&self, camera: &Camera) -> Vector2<f32>
+    {
+        camera.screen_to_world(self.mouse_position)
+    }
+
     pub fn to_full(self, camera: &Camera) -> ObjectCreateInfo<'a>
     {
         let projection_view = camera.projection_view();
@@ -83,6 +300,10 @@ pub struct DrawInfo<'a>
 {
     pub object_info: ObjectCreatePartialInfo<'a>,
     current_pipeline: Option<usize>,
+    interpolation: f32,
+    scissors: Vec<Scissor>,
+    // with the contents of the subpass currently being drawn in
+    framebuffers: Vec<(Arc<Framebuffer>, SubpassContents)>,
     pipelines: &'a [PipelineInfo]
 }
 
@@ -96,12 +317,146 @@ impl<'a> DrawInfo<'a>
         Self{
             object_info,
             current_pipeline: None,
+            interpolation: 1.0,
+            scissors: Vec::new(),
+            framebuffers: Vec::new(),
             pipelines
         }
     }
 
+    pub fn with_framebuffer(mut self, framebuffer: Arc<Framebuffer>, contents: SubpassContents) -> Self
+    {
+        self.framebuffers.push((framebuffer, contents));
+
+        self
+    }
+
+    // offscreen targets push their framebuffer while theyre being drawn into
+    pub(crate) fn push_framebuffer(&mut self, framebuffer: Arc<Framebuffer>, contents: SubpassContents)
+    {
+        self.framebuffers.push((framebuffer, contents));
+    }
+
+    pub(crate) fn pop_framebuffer(&mut self)
+    {
+        self.framebuffers.pop();
+    }
+
+    // secondary command buffers need its render pass for their inheritance info
+    pub fn framebuffer(&self) -> Option<&Arc<Framebuffer>>
+    {
+        self.framebuffers.last().map(|(framebuffer, _)| framebuffer)
+    }
+
+    // attachments of the framebuffer currently being drawn into, in render pass order
+    pub fn attachments(&self) -> &[Arc<ImageView>]
+    {
+        self.framebuffers.last().map(|(framebuffer, _)| framebuffer.attachments()).unwrap_or(&[])
+    }
+
+    pub fn subpass_contents(&self) -> SubpassContents
+    {
+        self.framebuffers.last().map(|(_, contents)| *contents).unwrap_or(SubpassContents::Inline)
+    }
+
+    // the next subpass has the same contents as the current one
+    pub fn next_subpass(&mut self)
+    {
+        self.next_subpass_with(self.subpass_contents());
+    }
+
+    // objects can only be drawn in inline subpasses, the others only take execute_commands
+    pub fn next_subpass_with(&mut self, contents: SubpassContents)
+    {
+        if let Some((_, current)) = self.framebuffers.last_mut()
+        {
+            *current = contents;
+        }
+
+        self.object_info.builder_wrapper.builder()
+            .next_subpass(
+                SubpassEndInfo::default(),
+                SubpassBeginInfo{
+                    contents,
+                    ..Default::default()
+                }
+            )
+            .unwrap();
+    }
+
+    // runs commands recorded on other threads, the subpass has to be begun with
+    // SubpassContents::SecondaryCommandBuffers (AppBuilder::with_secondary_command_buffers)
+    pub fn execute_commands(&mut self, command_buffer: Arc<SecondaryAutoCommandBuffer>)
+    {
+        assert_eq!(
+            self.subpass_contents(),
+            SubpassContents::SecondaryCommandBuffers,
+            "secondary command buffers cant be executed in an inline subpass"
+        );
+
+        self.object_info.builder_wrapper.builder()
+            .execute_commands(command_buffer)
+            .unwrap();
+    }
+
+    // binds an attachment written by an earlier subpass to the input attachment at location
+    pub fn bind_input_attachment(&mut self, location: UniformLocation, attachment: usize)
+    {
+        let view = self.attachments()[attachment].clone();
+
+        let layout = self.current_layout();
+        let descriptor_layout = layout.set_layouts().get(location.set as usize)
+            .unwrap()
+            .clone();
+
+        let descriptor_set = PersistentDescriptorSet::new(
+            &self.object_info.builder_wrapper.resource_uploader().descriptor_allocator,
+            descriptor_layout,
+            [WriteDescriptorSet::image_view(location.binding, view)],
+            []
+        ).unwrap();
+
+        self.object_info.builder_wrapper.builder()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                layout,
+                location.set,
+                descriptor_set
+            )
+            .unwrap();
+    }
+
+    pub fn with_interpolation(mut self, interpolation: f32) -> Self
+    {
+        self.interpolation = interpolation;
+
+        self
+    }
+
+    // subpixel offset for this frame already scaled into ndc
+    pub fn jitter(&self) -> Vector2<f32>
+    {
+        let [width, height] = self.object_info.size;
+
+        self.object_info.frame.subpixel_jitter()
+            .component_mul(&Vector2::new(2.0 / width, 2.0 / height))
+    }
+
+    // how far between the last and the next fixed update this frame is (0..1)
+    pub fn interpolation(&self) -> f32
+    {
+        self.interpolation
+    }
+
     pub fn bind_pipeline(&mut self, shader: ShaderId)
     {
+        // every object draw binds a pipeline first
+        assert_eq!(
+            self.subpass_contents(),
+            SubpassContents::Inline,
+            "objects cant be drawn in a subpass for secondary command buffers, switch with next_subpass_with"
+        );
+
         self.current_pipeline = Some(shader.get_raw());
 
         let pipeline = self.current_pipeline().pipeline.clone();
@@ -110,6 +465,15 @@ impl<'a> DrawInfo<'a>
         ).unwrap();
     }
 
+    pub fn with_pipeline<'b>(&'b mut self, shader: ShaderId) -> PipelineScope<'b, 'a>
+    {
+        let previous = self.current_pipeline_id();
+
+        self.bind_pipeline(shader);
+
+        PipelineScope{info: self, previous}
+    }
+
     pub fn current_pipeline_id(&self) -> Option<ShaderId>
     {
         self.current_pipeline.map(ShaderId)
@@ -140,6 +504,40 @@ impl<'a> DrawInfo<'a>
             .unwrap();
     }
 
+    // in debug builds checks that the bound pipeline has a push constant range
+    // for these stages that fits the constants at this offset
+    pub fn push_constants_checked<T: BufferContents>(
+        &mut self,
+        stages: ShaderStages,
+        offset: u32,
+        constants: T
+    )
+    {
+        let layout = self.current_layout();
+
+        let size = std::mem::size_of::<T>() as u32;
+
+        debug_assert!(
+            layout.push_constant_ranges().iter().any(|range|
+            {
+                range.stages.contains(stages)
+                    && range.offset <= offset
+                    && offset + size <= range.offset + range.size
+            }),
+            "pipeline #{} has no push constant range for {stages:?} at {offset}..{} (ranges: {:?})",
+            self.current_pipeline.unwrap(),
+            offset + size,
+            layout.push_constant_ranges()
+        );
+
+        self.object_info.builder_wrapper.builder().push_constants(
+                layout,
+                offset,
+                constants
+            )
+            .unwrap();
+    }
+
     #[allow(dead_code)]
     pub fn push_uniform_buffer<T: BufferContents>(
         &mut self,
@@ -157,6 +555,27 @@ impl<'a> DrawInfo<'a>
             .unwrap();
     }
 
+    pub fn set_uniform<T: BufferContents>(
+        &mut self,
+        location: UniformLocation,
+        value: T
+    )
+    {
+        let buffer = self.object_info.uniform_allocator.allocate_sized();
+        *buffer.write().unwrap() = value;
+
+        self.push_uniform_buffer(location, buffer);
+    }
+
+    pub fn push_camera_uniform(
+        &mut self,
+        location: UniformLocation,
+        camera: &Camera
+    )
+    {
+        self.set_uniform(location, camera.matrices());
+    }
+
     pub fn set_depth_test(&mut self, state: bool)
     {
         self.object_info.builder_wrapper.builder()
@@ -184,6 +603,106 @@ impl<'a> DrawInfo<'a>
             .set_scissor(0, vec![Scissor::default()].into())
             .unwrap();
     }
+
+    // clips to the intersection with the current scissor until its popped
+    pub fn push_scissor(&mut self, scissor: Scissor)
+    {
+        let scissor = match self.scissors.last()
+        {
+            Some(current) => Self::intersect_scissors(*current, scissor),
+            None => scissor
+        };
+
+        self.scissors.push(scissor);
+        self.set_scissor(scissor);
+    }
+
+    pub fn pop_scissor(&mut self)
+    {
+        self.scissors.pop();
+
+        match self.scissors.last()
+        {
+            Some(scissor) => self.set_scissor(*scissor),
+            None => self.reset_scissor()
+        }
+    }
+
+    pub fn current_scissor(&self) -> Option<Scissor>
+    {
+        self.scissors.last().copied()
+    }
+
+    fn intersect_scissors(a: Scissor, b: Scissor) -> Scissor
+    {
+        let end = |scissor: Scissor, index: usize|
+        {
+            scissor.offset[index].saturating_add(scissor.extent[index])
+        };
+
+        let offset = [0, 1].map(|index| a.offset[index].max(b.offset[index]));
+        let extent = [0, 1].map(|index|
+        {
+            end(a, index).min(end(b, index)).saturating_sub(offset[index])
+        });
+
+        Scissor{offset, extent}
+    }
+
+    // stays set for everything drawn after until its reset
+    pub fn set_depth_bias(&mut self, constant: f32, slope: f32)
+    {
+        self.object_info.builder_wrapper.builder()
+            .set_depth_bias(constant, 0.0, slope)
+            .unwrap();
+    }
+
+    pub fn reset_depth_bias(&mut self)
+    {
+        self.set_depth_bias(0.0, 0.0);
+    }
+}
+
+pub struct PipelineScope<'b, 'a>
+{
+    info: &'b mut DrawInfo<'a>,
+    previous: Option<ShaderId>
+}
+
+impl<'a> Deref for PipelineScope<'_, 'a>
+{
+    type Target = DrawInfo<'a>;
+
+    fn deref(&self) -> &Self::Target
+    {
+        self.info
+    }
+}
+
+impl DerefMut for PipelineScope<'_, '_>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        self.info
+    }
+}
+
+impl Drop for PipelineScope<'_, '_>
+{
+    fn drop(&mut self)
+    {
+        match self.previous
+        {
+            Some(previous) =>
+            {
+                if self.info.current_pipeline_id() != Some(previous)
+                {
+                    self.info.bind_pipeline(previous);
+                }
+            },
+            None => self.info.current_pipeline = None
+        }
+    }
 }
 
 pub type UpdateBuffersPartialInfo<'a> = ObjectCreatePartialInfo<'a>;
@@ -1,7 +1,12 @@
-use std::rc::Rc;
+use std::{
+    mem,
+    rc::Rc
+};
 
 use nalgebra::Vector2;
 
+use vulkano::buffer::{BufferContents, Subbuffer};
+
 use super::CommandBuilderType;
 
 use crate::{
@@ -49,6 +54,26 @@ impl<'a> BuilderWrapper<'a>
         self.resource_uploader.builder
     }
 
+    // update_buffer can only write 65536 bytes at a time, bigger data gets split up
+    pub fn update_buffer<T: BufferContents + Copy>(
+        &mut self,
+        subbuffer: &Subbuffer<[T]>,
+        data: &[T]
+    )
+    {
+        let chunk_size = 65536 / mem::size_of::<T>();
+
+        data.chunks(chunk_size).enumerate().for_each(|(index, values)|
+        {
+            let start = (index * chunk_size) as u64;
+            let end = start + values.len() as u64;
+
+            self.builder()
+                .update_buffer(subbuffer.clone().slice(start..end), Box::<[T]>::from(values))
+                .unwrap();
+        });
+    }
+
     pub fn text_factory<'b>(&'b mut self) -> TextFactory<'b, 'a>
     where
         'a: 'b
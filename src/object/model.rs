@@ -6,6 +6,8 @@ use nalgebra::Vector3;
 
 use strum::EnumIter;
 
+use crate::UvRect;
+
 
 type LineNumber = u32;
 
@@ -89,6 +91,11 @@ impl Model
         Self{vertices: Vec::new(), uvs: Vec::new()}
     }
 
+    pub fn empty() -> Self
+    {
+        Self::new()
+    }
+
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ParseError>
     {
         let parser = ObjParser::new();
@@ -112,6 +119,22 @@ impl Model
     }
 
     pub fn rectangle_with_uvs(uvs: Uvs, width: f32, height: f32) -> Self
+    {
+        Self::rectangle_with_flipped_uv_rect(uvs, width, height, UvRect::full())
+    }
+
+    // maps the corners to a part of the texture, like an image in an atlas
+    pub fn rectangle_with_uv_rect(width: f32, height: f32, rect: UvRect) -> Self
+    {
+        Self::rectangle_with_flipped_uv_rect(Uvs::Normal, width, height, rect)
+    }
+
+    pub fn rectangle_with_flipped_uv_rect(
+        uvs: Uvs,
+        width: f32,
+        height: f32,
+        rect: UvRect
+    ) -> Self
     {
         let (half_width, half_height) = (width / 2.0, height / 2.0);
 
@@ -124,6 +147,15 @@ impl Model
             [half_width, -half_height, 0.0]
         ];
 
+        let UvRect{min, max} = rect;
+        let to_rect = |[x, y]: [f32; 2]|
+        {
+            [
+                min[0] + (max[0] - min[0]) * x,
+                min[1] + (max[1] - min[1]) * y
+            ]
+        };
+
         let uvs = vec![
             uvs.bottom_left(),
             uvs.top_left(),
@@ -131,11 +163,76 @@ impl Model
             uvs.top_left(),
             uvs.top_right(),
             uvs.bottom_right()
-        ];
+        ].into_iter().map(to_rect).collect();
+
+        Self{vertices, uvs}
+    }
+
+    // closed fan around the center, uvs are the positions mapped into the bounding box
+    pub fn from_fan(center: Vector3<f32>, rim: &[Vector3<f32>]) -> Self
+    {
+        if rim.len() < 2
+        {
+            return Self::new();
+        }
+
+        let vertices: Vec<[f32; 3]> = (0..rim.len()).flat_map(|index|
+        {
+            let next = rim[(index + 1) % rim.len()];
+
+            [center.into(), rim[index].into(), next.into()]
+        }).collect();
+
+        let uvs = Self::bounds_uvs(&vertices);
 
         Self{vertices, uvs}
     }
 
+    // every 3 consecutive points make a triangle, u goes along the strip and v alternates sides
+    pub fn from_strip(points: &[Vector3<f32>]) -> Self
+    {
+        if points.len() < 3
+        {
+            return Self::new();
+        }
+
+        let pairs = ((points.len() - 1) / 2).max(1) as f32;
+        let uv = |index: usize|
+        {
+            [(index / 2) as f32 / pairs, (index % 2) as f32]
+        };
+
+        let (vertices, uvs) = points.windows(3).enumerate().flat_map(|(index, window)|
+        {
+            // keep the winding consistent by flipping every other triangle
+            let order = if index % 2 == 0 { [0, 1, 2] } else { [1, 0, 2] };
+
+            order.map(|offset| (<[f32; 3]>::from(window[offset]), uv(index + offset)))
+        }).unzip();
+
+        Self{vertices, uvs}
+    }
+
+    fn bounds_uvs(vertices: &[[f32; 3]]) -> Vec<[f32; 2]>
+    {
+        let (min, max) = vertices.iter().fold(
+            ([f32::MAX; 2], [f32::MIN; 2]),
+            |(min, max), vertex|
+            {
+                (
+                    [min[0].min(vertex[0]), min[1].min(vertex[1])],
+                    [max[0].max(vertex[0]), max[1].max(vertex[1])]
+                )
+            });
+
+        let size = [(max[0] - min[0]).max(f32::EPSILON), (max[1] - min[1]).max(f32::EPSILON)];
+
+        vertices.iter().map(|vertex|
+        {
+            [(vertex[0] - min[0]) / size[0], (vertex[1] - min[1]) / size[1]]
+        }).collect()
+    }
+
     pub fn shift(&mut self, offset: Vector3<f32>)
     {
         self.vertices.iter_mut().for_each(|vertex|
@@ -0,0 +1,157 @@
+use std::{
+	mem,
+	any::TypeId,
+	cell::RefCell,
+	rc::{Rc, Weak},
+	collections::VecDeque,
+	sync::Arc
+};
+
+use vulkano::{
+	buffer::{
+		BufferContents,
+		BufferUsage,
+		Subbuffer,
+		allocator::SubbufferAllocator
+	},
+	memory::allocator::MemoryTypeFilter
+};
+
+use crate::allocators::{subbuffer_allocator, ThisMemoryAllocator, DEFAULT_MEMORY_FILTER};
+
+
+type PooledSubbuffer = (TypeId, Subbuffer<[u8]>);
+
+type FreeList = RefCell<VecDeque<PooledSubbuffer>>;
+
+// one for each frame parity so writing the next frame doesnt race the one in flight
+pub type FrameSubbuffers<T> = [Subbuffer<[T]>; 2];
+
+fn push_free(free: &FreeList, pooled: PooledSubbuffer)
+{
+	// dont hold on to an unbounded amount of memory
+	const MAX_FREE: usize = 256;
+
+	let mut free = free.borrow_mut();
+	if free.len() == MAX_FREE
+	{
+		free.pop_front();
+	}
+
+	free.push_back(pooled);
+}
+
+// gives subbuffers back to the allocator they came from, for objects that
+// recycle their buffers when theyre dropped
+#[derive(Debug, Clone)]
+pub struct Recycler
+{
+	free: Weak<FreeList>
+}
+
+impl Recycler
+{
+	// does nothing if the allocator is already gone
+	pub fn recycle<T: BufferContents>(&self, subbuffer: Subbuffer<[T]>)
+	{
+		if let Some(free) = self.free.upgrade()
+		{
+			push_free(&free, (TypeId::of::<T>(), subbuffer.into_bytes()));
+		}
+	}
+}
+
+// subbuffers handed out by this can be kept for as long as the object needs them,
+// recycled ones get reused for the next allocation of the same type that fits,
+// transient ones are only valid until the next reset (which happens every frame)
+#[derive(Debug)]
+pub struct ObjectAllocator
+{
+	allocator: SubbufferAllocator,
+	free: Rc<FreeList>,
+	transient: RefCell<Vec<PooledSubbuffer>>
+}
+
+impl ObjectAllocator
+{
+	pub fn new(allocator: Arc<ThisMemoryAllocator>) -> Self
+	{
+		Self::new_with(
+			allocator,
+			BufferUsage::VERTEX_BUFFER | BufferUsage::INDEX_BUFFER | BufferUsage::TRANSFER_DST,
+			DEFAULT_MEMORY_FILTER
+		)
+	}
+
+	pub fn new_with(
+		allocator: Arc<ThisMemoryAllocator>,
+		buffer_usage: BufferUsage,
+		memory_type_filter: MemoryTypeFilter
+	) -> Self
+	{
+		Self{
+			allocator: subbuffer_allocator(allocator, buffer_usage, memory_type_filter),
+			free: Rc::new(RefCell::new(VecDeque::new())),
+			transient: RefCell::new(Vec::new())
+		}
+	}
+
+	pub fn subbuffer<T: BufferContents>(&self, size: u64) -> Subbuffer<[T]>
+	{
+		self.take_free(size).unwrap_or_else(|| self.allocator.allocate_slice(size).unwrap())
+	}
+
+	pub fn frame_subbuffers<T: BufferContents>(&self, size: u64) -> FrameSubbuffers<T>
+	{
+		[self.subbuffer(size), self.subbuffer(size)]
+	}
+
+	// the gpu might still be reading it this frame, so only give it back if
+	// nothing records commands with it after this
+	pub fn recycle<T: BufferContents>(&self, subbuffer: Subbuffer<[T]>)
+	{
+		push_free(&self.free, (TypeId::of::<T>(), subbuffer.into_bytes()));
+	}
+
+	pub fn recycler(&self) -> Recycler
+	{
+		Recycler{free: Rc::downgrade(&self.free)}
+	}
+
+	// for geometry that gets rebuilt every frame, like immediate mode ui
+	pub fn transient<T: BufferContents>(&self, size: u64) -> Subbuffer<[T]>
+	{
+		let subbuffer = self.subbuffer::<T>(size);
+
+		self.transient.borrow_mut().push((TypeId::of::<T>(), subbuffer.clone().into_bytes()));
+
+		subbuffer
+	}
+
+	// the previous frame has finished by the time the next one gets submitted,
+	// so its transient subbuffers can be handed out again
+	pub fn reset(&self)
+	{
+		let transient = self.transient.take();
+
+		transient.into_iter().for_each(|pooled| push_free(&self.free, pooled));
+	}
+
+	fn take_free<T: BufferContents>(&self, size: u64) -> Option<Subbuffer<[T]>>
+	{
+		let id = TypeId::of::<T>();
+
+		let mut free = self.free.borrow_mut();
+
+		let (index, _) = free.iter().enumerate()
+			.filter(|(_, (free_id, subbuffer))|
+			{
+				*free_id == id && subbuffer.size() >= size * mem::size_of::<T>() as u64
+			})
+			.min_by_key(|(_, (_, subbuffer))| subbuffer.size())?;
+
+		let (_, subbuffer) = free.swap_remove_back(index)?;
+
+		Some(subbuffer.reinterpret::<[T]>().slice(0..size))
+	}
+}
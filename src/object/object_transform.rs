@@ -11,6 +11,7 @@ pub struct ObjectTransform
 {
     transform: Transform,
     origin: Vector3<f32>,
+    pivot: Vector3<f32>,
     matrix: Matrix4<f32>
 }
 
@@ -33,19 +34,21 @@ impl ObjectTransform
 
     pub fn new(transform: Transform, origin: Vector3<f32>) -> Self
     {
-        let matrix = Self::calculate_matrix(&transform, &origin);
+        let pivot = Vector3::zeros();
+        let matrix = Self::calculate_matrix(&transform, &origin, &pivot);
 
-        Self{transform, origin, matrix}
+        Self{transform, origin, pivot, matrix}
     }
 
     pub fn recalculate_matrix(&mut self)
     {
-        self.matrix = Self::calculate_matrix(&self.transform, &self.origin);
+        self.matrix = Self::calculate_matrix(&self.transform, &self.origin, &self.pivot);
     }
 
     fn calculate_matrix(
         transform: &Transform,
-        origin: &Vector3<f32>
+        origin: &Vector3<f32>,
+        pivot: &Vector3<f32>
     ) -> Matrix4<f32>
     {
         let mut matrix = Matrix4::from_axis_angle(
@@ -58,6 +61,10 @@ impl ObjectTransform
         matrix.prepend_translation_mut(origin);
 
         matrix.prepend_nonuniform_scaling_mut(&transform.scale);
+
+        // rotation and scale happen around the pivot (in model space)
+        matrix.prepend_translation_mut(&-pivot);
+
         matrix.append_translation_mut(&transform.position);
 
         matrix
@@ -68,6 +75,18 @@ impl ObjectTransform
         self.origin = origin;
     }
 
+    pub fn pivot(&self) -> &Vector3<f32>
+    {
+        &self.pivot
+    }
+
+    pub fn set_pivot(&mut self, pivot: Vector3<f32>)
+    {
+        self.pivot = pivot;
+
+        self.recalculate_matrix();
+    }
+
     pub fn matrix(&self) -> Matrix4<f32>
     {
         self.matrix
@@ -86,8 +105,8 @@ impl ObjectTransform
 
         let mut stretch_matrix = Matrix4::identity();
         stretch_matrix.m11 = (s_x + s_y + s_x * angle_cos - s_y * angle_cos) / 2.0;
-        stretch_matrix.m12 = (-s_x * angle_sin + s_y * angle_sin) / 2.0;
-        stretch_matrix.m21 = (-s_x * angle_sin + s_y * angle_sin) / 2.0;
+        stretch_matrix.m12 = (s_x * angle_sin - s_y * angle_sin) / 2.0;
+        stretch_matrix.m21 = (s_x * angle_sin - s_y * angle_sin) / 2.0;
         stretch_matrix.m22 = (s_x + s_y - s_x * angle_cos + s_y * angle_cos) / 2.0;
 
         stretch_matrix
@@ -114,3 +133,31 @@ impl TransformContainer for ObjectTransform
         &mut self.transform
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use nalgebra::Vector4;
+
+
+    #[test]
+    fn stretch_along_angle()
+    {
+        let angle = 0.4_f32;
+
+        let transform = Transform{
+            stretch: Transform::stretch_along(angle, 2.0),
+            ..Default::default()
+        };
+
+        let matrix = ObjectTransform::calculate_stretch_matrix(&transform);
+
+        let along = Vector4::new(angle.cos(), angle.sin(), 0.0, 0.0);
+        let across = Vector4::new(-angle.sin(), angle.cos(), 0.0, 0.0);
+
+        assert!((matrix * along - along * 2.0).magnitude() < 0.0001, "{}", matrix * along);
+        assert!((matrix * across - across * 0.5).magnitude() < 0.0001, "{}", matrix * across);
+    }
+}
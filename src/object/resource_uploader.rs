@@ -10,7 +10,7 @@ use vulkano::{
 	}
 };
 
-use crate::PipelineInfo;
+use crate::{PipelineInfo, TransferQueue};
 
 
 pub struct ResourceUploader<'a>
@@ -19,5 +19,7 @@ pub struct ResourceUploader<'a>
 	pub descriptor_allocator: Arc<StandardDescriptorSetAllocator>,
 	pub sampler: Arc<Sampler>,
 	pub builder: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+	// none if the device doesnt have a separate transfer queue, texture copies go through it
+	pub transfer: Option<&'a TransferQueue>,
 	pub pipeline_infos: &'a [PipelineInfo]
 }
@@ -1,23 +1,38 @@
 use std::{
     fmt,
+    fs::File,
+    io::BufReader,
     path::Path,
+    time::Duration,
     sync::Arc
 };
 
 use nalgebra::Vector2;
 
 use vulkano::{
-    format::Format,
-    buffer::{Buffer, BufferUsage, BufferCreateInfo},
-    command_buffer::CopyBufferToImageInfo,
+    format::{Format, FormatFeatures},
+    device::DeviceOwned,
+    sync::Sharing,
+    buffer::{Buffer, Subbuffer, BufferUsage, BufferCreateInfo},
+    command_buffer::{CopyBufferToImageInfo, BlitImageInfo, ImageBlit},
     memory::allocator::{MemoryTypeFilter, AllocationCreateInfo},
     image::{
         max_mip_levels,
+        mip_level_extent,
         Image,
+        ImageAspects,
+        ImageSubresourceLayers,
         ImageType,
         ImageUsage,
         ImageCreateInfo,
-        view::ImageView
+        view::ImageView,
+        sampler::{
+            Filter,
+            SamplerAddressMode,
+            SamplerMipmapMode,
+            Sampler,
+            SamplerCreateInfo
+        }
     },
     descriptor_set::{
         PersistentDescriptorSet,
@@ -28,6 +43,10 @@ use vulkano::{
 use image::{
     ColorType,
     DynamicImage,
+    ImageFormat,
+    AnimationDecoder,
+    Frame,
+    codecs::{gif::GifDecoder, png::PngDecoder},
     error::ImageError
 };
 
@@ -51,6 +70,141 @@ impl Color
         Self{r, g, b, a}
     }
 
+    pub fn premultiplied(self) -> Self
+    {
+        let multiply = |x: u8|
+        {
+            ((x as u32 * self.a as u32 + 127) / 255) as u8
+        };
+
+        Self{r: multiply(self.r), g: multiply(self.g), b: multiply(self.b), a: self.a}
+    }
+
+    pub fn unpremultiplied(self) -> Self
+    {
+        if self.a == 0
+        {
+            return Self{r: 0, g: 0, b: 0, a: 0};
+        }
+
+        let divide = |x: u8|
+        {
+            ((x as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8
+        };
+
+        Self{r: divide(self.r), g: divide(self.g), b: divide(self.b), a: self.a}
+    }
+
+    pub fn multiply(self, other: Self) -> Self
+    {
+        let multiply = |a: u8, b: u8|
+        {
+            ((a as u32 * b as u32 + 127) / 255) as u8
+        };
+
+        Self{
+            r: multiply(self.r, other.r),
+            g: multiply(self.g, other.g),
+            b: multiply(self.b, other.b),
+            a: multiply(self.a, other.a)
+        }
+    }
+
+    // same as blend but mixes the colors in linear space like the gpu does with srgb textures
+    pub fn blend_linear(self, other: Self) -> Self
+    {
+        if self.a == 0
+        {
+            return other;
+        } else if other.a == 0
+        {
+            return self;
+        }
+
+        let to_f = |x: u8| x as f32 / 255.0;
+        let from_f = |x: f32| (x * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        let alpha = (to_f(other.a) + to_f(self.a) * (1.0 - to_f(other.a))).clamp(0.0, 1.0);
+
+        let mix = |a: u8, b: u8|
+        {
+            let a = srgb_to_linear(to_f(a));
+            let b = srgb_to_linear(to_f(b));
+
+            let mixed = lerp(a * to_f(self.a), b, to_f(other.a)) / alpha;
+
+            from_f(linear_to_srgb(mixed))
+        };
+
+        Self{
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: from_f(alpha)
+        }
+    }
+
+    // like multiply but keeps the original alpha
+    pub fn tint(self, other: Self) -> Self
+    {
+        Self{a: self.a, ..self.multiply(other)}
+    }
+
+    // hue in degrees, saturation and value from 0 to 1
+    pub fn to_hsv(&self) -> (f32, f32, f32)
+    {
+        let [r, g, b] = [self.r, self.g, self.b].map(|x| x as f32 / 255.0);
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+
+        let delta = max - min;
+
+        let hue = if delta == 0.0
+        {
+            0.0
+        } else if max == r
+        {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g
+        {
+            60.0 * ((b - r) / delta + 2.0)
+        } else
+        {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+        (hue, saturation, max)
+    }
+
+    // the alpha is always max, set it after if u need something else
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self
+    {
+        let hue = hue.rem_euclid(360.0);
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+
+        let (r, g, b) = match (hue / 60.0) as u32
+        {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+
+        let m = value - chroma;
+        let to_u8 = |x: f32| ((x + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        Self::new(to_u8(r), to_u8(g), to_u8(b), u8::MAX)
+    }
+
     pub fn blend(self, other: Self) -> Self
     {
         if self.a == 0
@@ -74,11 +228,6 @@ impl Color
         // or u could express this as lerp(self.alpha, 1.0, other.alpha)
         let alpha = (to_f(other.a) + to_f(self.a) * (1.0 - to_f(other.a))).clamp(0.0, 1.0);
 
-        fn lerp(a: f32, b: f32, t: f32) -> f32
-        {
-            a * (1.0 - t) + b * t
-        }
-
         let mix = |a, b|
         {
             let mixed = lerp(to_f(a) * to_f(self.a), to_f(b), to_f(other.a)) / alpha;
@@ -95,6 +244,68 @@ impl Color
     }
 }
 
+fn lerp(a: f32, b: f32, t: f32) -> f32
+{
+    a * (1.0 - t) + b * t
+}
+
+fn srgb_to_linear(x: f32) -> f32
+{
+    if x <= 0.04045
+    {
+        x / 12.92
+    } else
+    {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(x: f32) -> f32
+{
+    if x <= 0.0031308
+    {
+        x * 12.92
+    } else
+    {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter
+{
+    Nearest,
+    Bilinear
+}
+
+// textures get a full mip chain unless they were created with MipMode::None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MipMode
+{
+    #[default]
+    Full,
+    None
+}
+
+impl MipMode
+{
+    fn levels(self, extent: [u32; 3]) -> u32
+    {
+        match self
+        {
+            Self::Full => max_mip_levels(extent),
+            Self::None => 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageOutline
+{
+    pub color: Color,
+    pub size: f32
+}
+
 #[derive(Debug, Clone)]
 pub struct SimpleImage
 {
@@ -128,6 +339,41 @@ impl SimpleImage
         });
     }
 
+    // alpha of the tint color gets multiplied too, so use 255 to keep the original alpha
+    pub fn tint(&mut self, color: Color)
+    {
+        self.map(|pixel| pixel.multiply(color));
+    }
+
+    pub fn multiply(&mut self, other: &SimpleImage)
+    {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "images must be the same size"
+        );
+
+        self.colors.iter_mut().zip(other.colors.iter()).for_each(|(color, other)|
+        {
+            *color = color.multiply(*other);
+        });
+    }
+
+    pub fn premultiplied(&self) -> Self
+    {
+        let mut image = self.clone();
+        image.map(Color::premultiplied);
+
+        image
+    }
+
+    pub fn unpremultiplied(&self) -> Self
+    {
+        let mut image = self.clone();
+        image.map(Color::unpremultiplied);
+
+        image
+    }
+
     pub fn get_pixel(&self, x: usize, y: usize) -> Color
     {
         self.colors[self.index_of(x, y)]
@@ -178,6 +424,246 @@ impl SimpleImage
         flipped
     }
 
+    // grows the image by the outline size on every side
+    pub fn outlined(&self, outline: ImageOutline) -> Self
+    {
+        let radius = outline.size.ceil().max(0.0) as usize;
+
+        let width = self.width + radius * 2;
+        let height = self.height + radius * 2;
+
+        let mut distances = vec![f32::MAX; width * height];
+
+        for y in 0..self.height
+        {
+            for x in 0..self.width
+            {
+                if self.get_pixel(x, y).a == 0
+                {
+                    continue;
+                }
+
+                for offset_y in 0..=(radius * 2)
+                {
+                    for offset_x in 0..=(radius * 2)
+                    {
+                        let dx = offset_x as f32 - radius as f32;
+                        let dy = offset_y as f32 - radius as f32;
+
+                        let index = (y + offset_y) * width + (x + offset_x);
+
+                        let distance = &mut distances[index];
+                        *distance = distance.min((dx * dx + dy * dy).sqrt());
+                    }
+                }
+            }
+        }
+
+        let colors = distances.into_iter().map(|distance|
+        {
+            let coverage = (outline.size - distance + 0.5).clamp(0.0, 1.0);
+
+            let mut color = outline.color;
+            color.a = (color.a as f32 * coverage).round() as u8;
+
+            color
+        }).collect();
+
+        let mut image = Self::new(colors, width, height);
+        image.blit_blend_linear(self, radius, radius);
+
+        image
+    }
+
+    pub fn resized(&self, width: usize, height: usize, filter: ResizeFilter) -> Self
+    {
+        if self.colors.is_empty()
+        {
+            return Self::new(vec![Color::new(0, 0, 0, 0); width * height], width, height);
+        }
+
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+
+        let colors = (0..height).flat_map(|y|
+        {
+            (0..width).map(move |x| (x, y))
+        }).map(|(x, y)|
+        {
+            // sample at the pixel centers
+            let source_x = (x as f32 + 0.5) * scale_x - 0.5;
+            let source_y = (y as f32 + 0.5) * scale_y - 0.5;
+
+            match filter
+            {
+                ResizeFilter::Nearest =>
+                {
+                    let x = (source_x.round().max(0.0) as usize).min(self.width - 1);
+                    let y = (source_y.round().max(0.0) as usize).min(self.height - 1);
+
+                    self.get_pixel(x, y)
+                },
+                ResizeFilter::Bilinear => self.sample_bilinear(source_x, source_y)
+            }
+        }).collect();
+
+        Self::new(colors, width, height)
+    }
+
+    // rotates clockwise around the center (y points down), the image grows to fit the corners
+    pub fn rotated(&self, radians: f32) -> Self
+    {
+        if self.colors.is_empty()
+        {
+            return self.clone();
+        }
+
+        let (sin, cos) = radians.sin_cos();
+
+        let fit = |a: f32, b: f32|
+        {
+            // tiny errors shouldnt add a whole pixel
+            (a.abs() + b.abs() - 0.001).ceil().max(0.0) as usize
+        };
+
+        let (old_width, old_height) = (self.width as f32, self.height as f32);
+
+        let width = fit(old_width * cos, old_height * sin);
+        let height = fit(old_width * sin, old_height * cos);
+
+        let center = Vector2::new(width as f32, height as f32) / 2.0;
+        let old_center = Vector2::new(old_width, old_height) / 2.0;
+
+        let colors = (0..height).flat_map(|y|
+        {
+            (0..width).map(move |x| (x, y))
+        }).map(|(x, y)|
+        {
+            let offset = Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+
+            let source = Vector2::new(
+                offset.x * cos + offset.y * sin,
+                -offset.x * sin + offset.y * cos
+            ) + old_center;
+
+            let outside = |value: f32, size: f32| value < 0.0 || value > size;
+            if outside(source.x, old_width) || outside(source.y, old_height)
+            {
+                return Color::new(0, 0, 0, 0);
+            }
+
+            self.sample_bilinear(source.x - 0.5, source.y - 0.5)
+        }).collect();
+
+        Self::new(colors, width, height)
+    }
+
+    // position is in pixels, samples outside the image get clamped to the edge
+    pub fn sample_bilinear(&self, x: f32, y: f32) -> Color
+    {
+        let x = x.clamp(0.0, (self.width - 1) as f32);
+        let y = y.clamp(0.0, (self.height - 1) as f32);
+
+        let left = x.floor() as usize;
+        let top = y.floor() as usize;
+
+        let right = (left + 1).min(self.width - 1);
+        let bottom = (top + 1).min(self.height - 1);
+
+        let fraction_x = x.fract();
+        let fraction_y = y.fract();
+
+        // interpolating premultiplied colors so transparent pixels dont bleed their color
+        let get = |x, y|
+        {
+            let color = self.get_pixel(x, y).premultiplied();
+
+            [color.r, color.g, color.b, color.a].map(|x| x as f32)
+        };
+
+        let (top_left, top_right) = (get(left, top), get(right, top));
+        let (bottom_left, bottom_right) = (get(left, bottom), get(right, bottom));
+
+        let channel = |index: usize|
+        {
+            let top = lerp(top_left[index], top_right[index], fraction_x);
+            let bottom = lerp(bottom_left[index], bottom_right[index], fraction_x);
+
+            lerp(top, bottom, fraction_y).round().clamp(0.0, 255.0) as u8
+        };
+
+        Color::new(channel(0), channel(1), channel(2), channel(3)).unpremultiplied()
+    }
+
+    // separable gaussian where radius is how far the kernel reaches (about 3 sigma)
+    pub fn blur(&self, radius: f32) -> Self
+    {
+        if radius <= 0.0 || self.colors.is_empty()
+        {
+            return self.clone();
+        }
+
+        let extent = radius.ceil() as i32;
+        let sigma = radius / 3.0;
+
+        let kernel = (-extent..=extent).map(|offset|
+        {
+            let offset = offset as f32;
+
+            (-(offset * offset) / (2.0 * sigma * sigma)).exp()
+        }).collect::<Vec<_>>();
+
+        let kernel_sum: f32 = kernel.iter().sum();
+        let kernel = kernel.into_iter().map(|x| x / kernel_sum).collect::<Vec<_>>();
+
+        // blurring premultiplied colors stops transparent pixels from darkening the edges
+        let pixels = self.colors.iter().map(|color|
+        {
+            let color = color.premultiplied();
+
+            [color.r, color.g, color.b, color.a].map(|x| x as f32)
+        }).collect::<Vec<_>>();
+
+        let pass = |pixels: &[[f32; 4]], horizontal: bool|
+        {
+            (0..self.height).flat_map(|y|
+            {
+                (0..self.width).map(move |x| (x, y))
+            }).map(|(x, y)|
+            {
+                kernel.iter().enumerate().fold([0.0; 4], |mut acc, (index, weight)|
+                {
+                    let offset = index as i32 - extent;
+
+                    let (sample_x, sample_y) = if horizontal
+                    {
+                        ((x as i32 + offset).clamp(0, self.width as i32 - 1) as usize, y)
+                    } else
+                    {
+                        (x, (y as i32 + offset).clamp(0, self.height as i32 - 1) as usize)
+                    };
+
+                    let sample = pixels[self.index_of(sample_x, sample_y)];
+                    acc.iter_mut().zip(sample).for_each(|(acc, value)| *acc += value * weight);
+
+                    acc
+                })
+            }).collect::<Vec<_>>()
+        };
+
+        let pixels = pass(&pixels, true);
+        let pixels = pass(&pixels, false);
+
+        let colors = pixels.into_iter().map(|pixel|
+        {
+            let [r, g, b, a] = pixel.map(|x| x.round().clamp(0.0, 255.0) as u8);
+
+            Color::new(r, g, b, a).unpremultiplied()
+        }).collect();
+
+        Self::new(colors, self.width, self.height)
+    }
+
     pub fn blit(&mut self, other: &Self, origin_x: usize, origin_y: usize)
     {
         self.blit_inner(other, origin_x, origin_y, |this, p, x, y|
@@ -194,6 +680,20 @@ impl SimpleImage
         });
     }
 
+    pub fn blit_blend_linear(&mut self, other: &Self, origin_x: usize, origin_y: usize)
+    {
+        self.blit_inner(other, origin_x, origin_y, |this, p, x, y|
+        {
+            if x >= this.width || y >= this.height
+            {
+                return;
+            }
+
+            let index = this.index_of(x, y);
+            this.colors[index] = this.colors[index].blend_linear(p);
+        });
+    }
+
     fn blit_inner<F>(&mut self, other: &Self, origin_x: usize, origin_y: usize, mut op: F)
     where
         F: FnMut(&mut Self, Color, usize, usize)
@@ -267,6 +767,53 @@ impl RgbaImage
         Ok(Self{data, width, height})
     }
 
+    pub fn load_frames(filepath: impl AsRef<Path>) -> Result<Vec<(Self, Duration)>, ImageError>
+    {
+        let filepath = filepath.as_ref();
+
+        let reader = || -> Result<_, ImageError>
+        {
+            Ok(BufReader::new(File::open(filepath)?))
+        };
+
+        let from_frames = |frames: Vec<Frame>|
+        {
+            frames.into_iter().map(|frame|
+            {
+                let delay = Duration::from(frame.delay());
+
+                (Self::from(frame.into_buffer()), delay)
+            }).collect()
+        };
+
+        match ImageFormat::from_path(filepath)?
+        {
+            ImageFormat::Gif =>
+            {
+                let frames = GifDecoder::new(reader()?)?.into_frames().collect_frames()?;
+
+                Ok(from_frames(frames))
+            },
+            ImageFormat::Png =>
+            {
+                let decoder = PngDecoder::new(reader()?)?;
+
+                if decoder.is_apng()
+                {
+                    let frames = decoder.apng().into_frames().collect_frames()?;
+
+                    Ok(from_frames(frames))
+                } else
+                {
+                    let image = DynamicImage::from_decoder(decoder)?.into_rgba8();
+
+                    Ok(vec![(Self::from(image), Duration::ZERO)])
+                }
+            },
+            _ => Ok(vec![(Self::load(filepath)?, Duration::ZERO)])
+        }
+    }
+
     pub fn save(&self, filename: impl AsRef<Path>) -> Result<(), ImageError>
     {
         image::save_buffer(filename, &self.data, self.width, self.height, ColorType::Rgba8)
@@ -284,6 +831,17 @@ impl From<SimpleImage> for RgbaImage
     }
 }
 
+impl From<image::RgbaImage> for RgbaImage
+{
+    fn from(other: image::RgbaImage) -> Self
+    {
+        let width = other.width();
+        let height = other.height();
+
+        Self::new(other.into_raw(), width, height)
+    }
+}
+
 impl fmt::Debug for RgbaImage
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
@@ -300,6 +858,7 @@ pub struct Texture
 {
     view: Arc<ImageView>,
     descriptor_set: Arc<PersistentDescriptorSet>,
+    sampler: Option<Arc<Sampler>>,
     location: UniformLocation,
     shader: ShaderId
 }
@@ -313,24 +872,162 @@ impl Texture
         shader: ShaderId
     ) -> Self
     {
-        let view = Self::calculate_descriptor_set(resource_uploader, &image);
+        Self::new_with_sampler(resource_uploader, image, None, location, shader)
+    }
+
+    pub fn new_with_mips(
+        resource_uploader: &mut ResourceUploader,
+        image: RgbaImage,
+        mip: MipMode,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        Self::new_inner(resource_uploader, image, None, mip, location, shader)
+    }
+
+    pub fn new_with_sampler(
+        resource_uploader: &mut ResourceUploader,
+        image: RgbaImage,
+        sampler: Option<Arc<Sampler>>,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        Self::new_inner(resource_uploader, image, sampler, MipMode::default(), location, shader)
+    }
+
+    fn new_inner(
+        resource_uploader: &mut ResourceUploader,
+        image: RgbaImage,
+        sampler: Option<Arc<Sampler>>,
+        mip: MipMode,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        let view = Self::calculate_descriptor_set(resource_uploader, &image, mip);
 
         let descriptor_set = Self::calculate_persistent_set(
             view.clone(),
             resource_uploader,
+            sampler.clone(),
             location,
             shader
         );
 
-        Self{view, descriptor_set, location, shader}
+        Self{view, descriptor_set, sampler, location, shader}
     }
 
-    fn calculate_descriptor_set(
+    pub fn outlined(
         resource_uploader: &mut ResourceUploader,
-        image: &RgbaImage
-    ) -> Arc<ImageView>
+        source: &SimpleImage,
+        outline: ImageOutline,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        Self::new(resource_uploader, source.outlined(outline).into(), location, shader)
+    }
+
+    pub fn repeating(
+        resource_uploader: &mut ResourceUploader,
+        image: RgbaImage,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        let sampler = Sampler::new(
+            resource_uploader.allocator.device().clone(),
+            SamplerCreateInfo{
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Linear,
+                mipmap_mode: SamplerMipmapMode::Linear,
+                address_mode: [SamplerAddressMode::Repeat; 3],
+                ..Default::default()
+            }
+        ).unwrap();
+
+        Self::new_with_sampler(resource_uploader, image, Some(sampler), location, shader)
+    }
+
+    pub fn from_view(
+        resource_uploader: &ResourceUploader,
+        view: Arc<ImageView>,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        let descriptor_set = Self::calculate_persistent_set(
+            view.clone(),
+            resource_uploader,
+            None,
+            location,
+            shader
+        );
+
+        Self{view, descriptor_set, sampler: None, location, shader}
+    }
+
+    pub fn solid(
+        resource_uploader: &mut ResourceUploader,
+        color: Color,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        let image = SimpleImage::new(vec![color], 1, 1);
+
+        Self::new(resource_uploader, image.into(), location, shader)
+    }
+
+    // packs all the images into 1 staging buffer instead of allocating one per texture
+    pub fn new_many<I>(
+        resource_uploader: &mut ResourceUploader,
+        images: I,
+        location: UniformLocation
+    ) -> Vec<Self>
+    where
+        I: IntoIterator<Item=(RgbaImage, ShaderId)>
+    {
+        let images: Vec<_> = images.into_iter().collect();
+
+        if images.is_empty()
+        {
+            return Vec::new();
+        }
+
+        let data: Vec<u8> = images.iter().flat_map(|(image, _)| image.data.iter().copied()).collect();
+        let buffer = Self::staging_buffer(resource_uploader, data.into_iter());
+
+        let mut offset = 0;
+        images.into_iter().map(|(image, shader)|
+        {
+            let size = image.data.len() as u64;
+            let slice = buffer.clone().slice(offset..offset + size);
+
+            offset += size;
+
+            let view = Self::upload_image(resource_uploader, slice, &image, MipMode::default());
+
+            let descriptor_set = Self::calculate_persistent_set(
+                view.clone(),
+                resource_uploader,
+                None,
+                location,
+                shader
+            );
+
+            Self{view, descriptor_set, sampler: None, location, shader}
+        }).collect()
+    }
+
+    fn staging_buffer(
+        resource_uploader: &ResourceUploader,
+        data: impl ExactSizeIterator<Item=u8>
+    ) -> Subbuffer<[u8]>
     {
-        let buffer = Buffer::from_iter(
+        Buffer::from_iter(
             resource_uploader.allocator.clone(),
             BufferCreateInfo{
                 usage: BufferUsage::TRANSFER_SRC,
@@ -341,31 +1038,121 @@ impl Texture
                     | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
                 ..Default::default()
             },
-            image.data.iter().copied()
-        ).unwrap();
+            data
+        ).unwrap()
+    }
+
+    fn calculate_descriptor_set(
+        resource_uploader: &mut ResourceUploader,
+        image: &RgbaImage,
+        mip: MipMode
+    ) -> Arc<ImageView>
+    {
+        let buffer = Self::staging_buffer(resource_uploader, image.data.iter().copied());
+
+        Self::upload_image(resource_uploader, buffer, image, mip)
+    }
 
+    fn upload_image(
+        resource_uploader: &mut ResourceUploader,
+        buffer: Subbuffer<[u8]>,
+        image: &RgbaImage,
+        mip: MipMode
+    ) -> Arc<ImageView>
+    {
         let extent = [image.width, image.height, 1];
 
+        let mip_levels = mip.levels(extent);
+
+        // the lower mips get blitted from the base level
+        let usage = if mip_levels > 1
+        {
+            ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST | ImageUsage::TRANSFER_SRC
+        } else
+        {
+            ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST
+        };
+
+        // the copy runs on the transfer queue and the mips get blitted on the graphics queue,
+        // the graphics submission waits on the transfers so the base level is there by then
+        let transfer = resource_uploader.transfer;
+
+        let sharing = transfer.map(|transfer|
+        {
+            Sharing::Concurrent(transfer.queue_families().into_iter().collect())
+        }).unwrap_or(Sharing::Exclusive);
+
         let image = Image::new(
             resource_uploader.allocator.clone(),
             ImageCreateInfo{
                 image_type: ImageType::Dim2d,
                 format: Format::R8G8B8A8_SRGB,
                 extent,
-                mip_levels: max_mip_levels(extent),
-                usage: ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST,
+                mip_levels,
+                usage,
+                sharing,
                 ..Default::default()
             },
             AllocationCreateInfo::default()
         ).unwrap();
 
-        resource_uploader.builder
-            .copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(buffer, image.clone()))
-            .unwrap();
+        let copy_info = CopyBufferToImageInfo::buffer_image(buffer, image.clone());
+
+        if let Some(transfer) = transfer
+        {
+            transfer.builder().copy_buffer_to_image(copy_info).unwrap();
+        } else
+        {
+            resource_uploader.builder.copy_buffer_to_image(copy_info).unwrap();
+        }
+
+        Self::generate_mips(resource_uploader, image.clone());
 
         ImageView::new_default(image).unwrap()
     }
 
+    // the command buffer builder takes care of the barriers between the levels
+    fn generate_mips(resource_uploader: &mut ResourceUploader, image: Arc<Image>)
+    {
+        let linear = image.device().physical_device()
+            .format_properties(image.format())
+            .map(|properties|
+            {
+                properties.optimal_tiling_features.intersects(FormatFeatures::SAMPLED_IMAGE_FILTER_LINEAR)
+            })
+            .unwrap_or(false);
+
+        let filter = if linear { Filter::Linear } else { Filter::Nearest };
+
+        let extent = image.extent();
+        let layers = |mip_level|
+        {
+            ImageSubresourceLayers{
+                aspects: ImageAspects::COLOR,
+                mip_level,
+                array_layers: 0..1
+            }
+        };
+
+        (1..image.mip_levels()).for_each(|level|
+        {
+            let src_extent = mip_level_extent(extent, level - 1).unwrap();
+            let dst_extent = mip_level_extent(extent, level).unwrap();
+
+            resource_uploader.builder.blit_image(BlitImageInfo{
+                regions: [ImageBlit{
+                    src_subresource: layers(level - 1),
+                    src_offsets: [[0; 3], src_extent],
+                    dst_subresource: layers(level),
+                    dst_offsets: [[0; 3], dst_extent],
+                    ..Default::default()
+                }].into(),
+                filter,
+                ..BlitImageInfo::images(image.clone(), image.clone())
+            }).unwrap();
+        });
+    }
+
     pub fn image(&self) -> &Arc<Image>
     {
         self.view.image()
@@ -388,6 +1175,7 @@ impl Texture
         self.descriptor_set = Self::calculate_persistent_set(
             self.view.clone(),
             resource_uploader,
+            self.sampler.clone(),
             self.location,
             self.shader
         );
@@ -396,6 +1184,7 @@ impl Texture
     fn calculate_persistent_set(
         view: Arc<ImageView>,
         resource_uploader: &ResourceUploader,
+        sampler: Option<Arc<Sampler>>,
         location: UniformLocation,
         shader: ShaderId
     ) -> Arc<PersistentDescriptorSet>
@@ -410,13 +1199,20 @@ impl Texture
             descriptor_layout,
             [
                 WriteDescriptorSet::image_view_sampler(
-                    location.binding, view, resource_uploader.sampler.clone()
+                    location.binding,
+                    view,
+                    sampler.unwrap_or_else(|| resource_uploader.sampler.clone())
                 )
             ],
             []
         ).unwrap()
     }
 
+    pub fn location(&self) -> UniformLocation
+    {
+        self.location
+    }
+
     pub fn descriptor_set(&self) -> Arc<PersistentDescriptorSet>
     {
         self.descriptor_set.clone()
@@ -431,3 +1227,182 @@ impl fmt::Debug for Texture
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use vulkano::{
+        VulkanLibrary,
+        instance::{Instance, InstanceCreateInfo, InstanceCreateFlags},
+        device::{Device, DeviceCreateInfo, Queue, QueueCreateInfo, QueueFlags},
+        memory::allocator::StandardMemoryAllocator,
+        descriptor_set::allocator::StandardDescriptorSetAllocator,
+        command_buffer::{
+            AutoCommandBufferBuilder,
+            BufferImageCopy,
+            CommandBufferUsage,
+            CopyImageToBufferInfo,
+            PrimaryCommandBufferAbstract,
+            allocator::StandardCommandBufferAllocator
+        },
+        sync::GpuFuture
+    };
+
+    use super::*;
+
+
+    fn transposed(image: &SimpleImage) -> SimpleImage
+    {
+        let colors = (0..image.width).flat_map(|y|
+        {
+            (0..image.height).map(move |x| image.get_pixel(y, x))
+        }).collect();
+
+        SimpleImage::new(colors, image.height, image.width)
+    }
+
+    #[test]
+    fn rotated_quarter_turn()
+    {
+        let colors = (0..6).map(|index| Color::new(index * 40, 255 - index * 40, index * 10, 255)).collect();
+        let image = SimpleImage::new(colors, 3, 2);
+
+        let rotated = image.rotated(std::f32::consts::FRAC_PI_2);
+        let expected = transposed(&image).flipped_horizontal();
+
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+
+        rotated.colors.iter().zip(expected.colors.iter()).for_each(|(a, b)|
+        {
+            assert_eq!([a.r, a.g, a.b, a.a], [b.r, b.g, b.b, b.a]);
+        });
+    }
+
+    fn test_device() -> (Arc<Device>, Arc<Queue>)
+    {
+        let instance = Instance::new(
+            VulkanLibrary::new().unwrap(),
+            InstanceCreateInfo{
+                flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
+                ..Default::default()
+            }
+        ).unwrap();
+
+        let physical_device = instance.enumerate_physical_devices().unwrap()
+            .next()
+            .expect("no vulkan device");
+
+        let queue_family_index = physical_device.queue_family_properties()
+            .iter()
+            .position(|queue| queue.queue_flags.contains(QueueFlags::GRAPHICS))
+            .unwrap() as u32;
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo{
+                queue_create_infos: vec![QueueCreateInfo{
+                    queue_family_index,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }
+        ).unwrap();
+
+        (device, queues.next().unwrap())
+    }
+
+    // needs an actual vulkan device (or a software one like lavapipe), ci machines dont have one
+    #[test]
+    #[ignore = "needs a vulkan device, run with cargo test -- --ignored"]
+    fn mips_are_averaged()
+    {
+        let (device, queue) = test_device();
+
+        let allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let command_allocator = StandardCommandBufferAllocator::new(device.clone(), Default::default());
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &command_allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit
+        ).unwrap();
+
+        let size = 256;
+        let colors = (0..size * size).map(|index|
+        {
+            let (x, y) = (index % size, index / size);
+
+            if (x + y) % 2 == 0 { Color::new(0, 0, 0, 255) } else { Color::new(255, 255, 255, 255) }
+        }).collect();
+
+        let image: RgbaImage = SimpleImage::new(colors, size, size).into();
+
+        let view = {
+            let mut resource_uploader = ResourceUploader{
+                allocator: allocator.clone(),
+                descriptor_allocator: Arc::new(StandardDescriptorSetAllocator::new(
+                    device.clone(),
+                    Default::default()
+                )),
+                sampler: Sampler::new(device.clone(), SamplerCreateInfo::simple_repeat_linear()).unwrap(),
+                builder: &mut builder,
+                transfer: None,
+                pipeline_infos: &[]
+            };
+
+            Texture::calculate_descriptor_set(&mut resource_uploader, &image, MipMode::Full)
+        };
+
+        let image = view.image().clone();
+        let level = image.mip_levels() - 1;
+
+        assert_eq!(level, 8);
+
+        let [width, height, _] = mip_level_extent(image.extent(), level).unwrap();
+
+        let buffer = Buffer::new_slice::<u8>(
+            allocator,
+            BufferCreateInfo{
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo{
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            width as u64 * height as u64 * 4
+        ).unwrap();
+
+        builder.copy_image_to_buffer(CopyImageToBufferInfo{
+            regions: [BufferImageCopy{
+                image_subresource: ImageSubresourceLayers{
+                    aspects: ImageAspects::COLOR,
+                    mip_level: level,
+                    array_layers: 0..1
+                },
+                image_extent: [width, height, 1],
+                ..Default::default()
+            }].into(),
+            ..CopyImageToBufferInfo::image_buffer(image, buffer.clone())
+        }).unwrap();
+
+        builder.build().unwrap()
+            .execute(queue)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let data = buffer.read().unwrap();
+
+        // half black and half white averages to grey (brighter than 128 since its srgb)
+        data.chunks_exact(4).for_each(|pixel|
+        {
+            assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2], "{pixel:?} isnt grey");
+            assert!((100..=200).contains(&pixel[0]), "{pixel:?} isnt an average");
+            assert_eq!(pixel[3], 255);
+        });
+    }
+}
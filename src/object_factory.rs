@@ -15,6 +15,11 @@ use super::{
 
 use crate::{
     SolidObject,
+    DynamicObject,
+    InstancedObject,
+    SpriteBatch,
+    SdfObject,
+    SdfParams,
     transform::Transform
 };
 
@@ -39,6 +44,11 @@ impl ObjectFactory
 		Self{allocator}
 	}
 
+	pub(crate) fn allocator(&self) -> &ObjectAllocator
+	{
+		&self.allocator
+	}
+
 	pub fn create(&self, info: ObjectInfo) -> Object
 	{
 		let object_transform = ObjectTransform::new_transformed(info.transform);
@@ -51,6 +61,19 @@ impl ObjectFactory
 		)
 	}
 
+	// for things that get rebuilt every frame, dont keep it around after drawing
+	pub fn create_transient(&self, info: ObjectInfo) -> Object
+	{
+		let object_transform = ObjectTransform::new_transformed(info.transform);
+
+		Object::new_transient(
+			info.model,
+			info.texture,
+			object_transform,
+			&self.allocator
+		)
+	}
+
     pub fn create_solid(&self, model: Arc<RwLock<Model>>, transform: Transform) -> SolidObject
     {
         SolidObject::new(
@@ -60,6 +83,47 @@ impl ObjectFactory
         )
     }
 
+    pub fn create_dynamic(
+        &self,
+        model: Model,
+        texture: Arc<RwLock<Texture>>,
+        transform: Transform,
+        capacity: usize
+    ) -> DynamicObject
+    {
+        DynamicObject::new(
+            model,
+            texture,
+            ObjectTransform::new_transformed(transform),
+            capacity,
+            &self.allocator
+        )
+    }
+
+    pub fn create_instanced(
+        &self,
+        model: Arc<RwLock<Model>>,
+        texture: Arc<RwLock<Texture>>,
+        transforms: &[Transform]
+    ) -> InstancedObject
+    {
+        InstancedObject::new(model, texture, transforms, &self.allocator)
+    }
+
+    pub fn create_sprite_batch(&self, capacity: usize) -> SpriteBatch
+    {
+        SpriteBatch::new(capacity, &self.allocator)
+    }
+
+    pub fn create_sdf(&self, params: SdfParams, transform: Transform) -> SdfObject
+    {
+        SdfObject::new(
+            params,
+            ObjectTransform::new_transformed(transform),
+            &self.allocator
+        )
+    }
+
     pub fn create_occluding(&self, transform: Transform) -> OccludingPlane
     {
 		let object_transform = ObjectTransform::new_transformed(transform);
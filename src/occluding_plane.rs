@@ -18,11 +18,100 @@ use crate::{
 };
 
 
+// 4 lines with 2 points each
+#[cfg(debug_assertions)]
+const DEBUG_VERTICES: u64 = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct OccluderPoints
+{
+    pub bottom_left: Vector3<f32>,
+    pub bottom_right: Vector3<f32>,
+    pub top_left: Vector3<f32>,
+    pub top_right: Vector3<f32>
+}
+
+impl OccluderPoints
+{
+    // the top points r at infinity so these r just 1 length of the shadow away from the bottom,
+    // the shadow goes along the ground so the tops have the same z as the bottoms
+    pub fn from_bottom(
+        bottom_left: Vector3<f32>,
+        bottom_right: Vector3<f32>,
+        origin: Vector3<f32>
+    ) -> Self
+    {
+        Self{
+            bottom_left,
+            bottom_right,
+            top_left: bottom_left + Self::shadow_direction(bottom_left, origin),
+            top_right: bottom_right + Self::shadow_direction(bottom_right, origin)
+        }
+    }
+
+    fn shadow_direction(point: Vector3<f32>, origin: Vector3<f32>) -> Vector3<f32>
+    {
+        let mut direction = point - origin;
+        direction.z = 0.0;
+
+        direction
+    }
+
+    pub fn lines(&self) -> [(Vector3<f32>, Vector3<f32>); 4]
+    {
+        [
+            (self.bottom_left, self.bottom_right),
+            (self.bottom_right, self.top_right),
+            (self.top_right, self.top_left),
+            (self.top_left, self.bottom_left)
+        ]
+    }
+
+    // picks the 2 points that look the furthest apart from the origin and extends them away from it
+    pub fn silhouette(points: &[Vector3<f32>], origin: Vector3<f32>) -> Option<Self>
+    {
+        let angle_between = |a: Vector3<f32>, b: Vector3<f32>|
+        {
+            let a = (a - origin).xy();
+            let b = (b - origin).xy();
+
+            a.perp(&b).atan2(a.dot(&b))
+        };
+
+        let (left, right) = points.iter().enumerate().flat_map(|(index, a)|
+        {
+            points[index + 1..].iter().map(move |b| (*a, *b))
+        }).max_by(|(a0, b0), (a1, b1)|
+        {
+            angle_between(*a0, *b0).abs().total_cmp(&angle_between(*a1, *b1).abs())
+        })?;
+
+        let (bottom_left, bottom_right) = if angle_between(left, right) < 0.0
+        {
+            (right, left)
+        } else
+        {
+            (left, right)
+        };
+
+        Some(Self::from_bottom(bottom_left, bottom_right, origin))
+    }
+}
+
+pub trait ShadowCaster
+{
+    fn shadow_points(&self, origin: Vector3<f32>) -> Option<OccluderPoints>;
+}
+
 pub struct OccludingPlane
 {
     transform: ObjectTransform,
     subbuffer: Subbuffer<[SimpleVertex]>,
     #[cfg(debug_assertions)]
+    debug_points: Option<OccluderPoints>,
+    #[cfg(debug_assertions)]
+    debug_subbuffer: Subbuffer<[SimpleVertex]>,
+    #[cfg(debug_assertions)]
     updated_buffers: bool
 }
 
@@ -49,6 +138,10 @@ impl OccludingPlane
             transform,
             subbuffer,
             #[cfg(debug_assertions)]
+            debug_points: None,
+            #[cfg(debug_assertions)]
+            debug_subbuffer: allocator.subbuffer(DEBUG_VERTICES),
+            #[cfg(debug_assertions)]
             updated_buffers: false
         }
     }
@@ -59,26 +152,18 @@ impl OccludingPlane
         projection_view: Matrix4<f32>
     ) -> Box<[SimpleVertex]>
     {
-        let transform = self.transform.matrix();
-
-        let un_bottom_left = transform * Vector4::new(-0.5, 0.0, 0.0, 1.0);
-        let un_bottom_right = transform * Vector4::new(0.5, 0.0, 0.0, 1.0);
+        let points = self.points(origin);
 
         let with_w = |values: Vector3<f32>, w|
         {
             Vector4::new(values.x, values.y, values.z, w)
         };
 
-        let mut un_top_left = un_bottom_left.xyz() - origin;
-        un_top_left.z = 0.0;
-
-        let mut un_top_right = un_bottom_right.xyz() - origin;
-        un_top_right.z = 0.0;
-
-        let bottom_left = projection_view * un_bottom_left;
-        let mut bottom_right = projection_view * un_bottom_right;
-        let mut top_left = projection_view * with_w(un_top_left, 0.0);
-        let mut top_right = projection_view * with_w(un_top_right, 0.0);
+        // the top points r directions so they get projected to infinity
+        let bottom_left = projection_view * with_w(points.bottom_left, 1.0);
+        let mut bottom_right = projection_view * with_w(points.bottom_right, 1.0);
+        let mut top_left = projection_view * with_w(points.top_left - points.bottom_left, 0.0);
+        let mut top_right = projection_view * with_w(points.top_right - points.bottom_right, 0.0);
 
         {
             let z = bottom_left.z;
@@ -89,8 +174,7 @@ impl OccludingPlane
         }
 
         let winding = {
-            let un_top_left = un_bottom_left.xyz() + un_bottom_left.xyz() - origin;
-            let top_left = (projection_view * with_w(un_top_left, 1.0)).xy();
+            let top_left = (projection_view * with_w(points.top_left, 1.0)).xy();
 
             let bottom_left = bottom_left.xy();
             let bottom_right = bottom_right.xy();
@@ -131,6 +215,36 @@ impl OccludingPlane
         }).collect::<Box<[_]>>()
     }
 
+    pub fn points(&self, origin: Vector3<f32>) -> OccluderPoints
+    {
+        let transform = self.transform.matrix();
+
+        let bottom_left = (transform * Vector4::new(-0.5, 0.0, 0.0, 1.0)).xyz();
+        let bottom_right = (transform * Vector4::new(0.5, 0.0, 0.0, 1.0)).xyz();
+
+        OccluderPoints::from_bottom(bottom_left, bottom_right, origin)
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn debug_points(&self) -> Option<OccluderPoints>
+    {
+        self.debug_points
+    }
+
+    #[cfg(debug_assertions)]
+    fn calculate_debug_vertices(
+        points: &OccluderPoints,
+        projection_view: Matrix4<f32>
+    ) -> Box<[SimpleVertex]>
+    {
+        points.lines().into_iter().flat_map(|(a, b)| [a, b]).map(|point|
+        {
+            let position = projection_view * Vector4::new(point.x, point.y, point.z, 1.0);
+
+            SimpleVertex{position: position.into()}
+        }).collect()
+    }
+
     pub fn update_buffers(
         &mut self,
         origin: Vector3<f32>,
@@ -139,6 +253,18 @@ impl OccludingPlane
     {
         self.set_updated(&info.partial);
 
+        #[cfg(debug_assertions)]
+        {
+            let points = self.points(origin);
+            self.debug_points = Some(points);
+
+            info.partial.builder_wrapper.builder()
+                .update_buffer(
+                    self.debug_subbuffer.clone(),
+                    Self::calculate_debug_vertices(&points, info.projection_view)
+                ).unwrap();
+        }
+
         info.partial.builder_wrapper.builder()
             .update_buffer(
                 self.subbuffer.clone(),
@@ -159,6 +285,20 @@ impl OccludingPlane
             .unwrap();
     }
 
+    // draws the outline of the shadow as 4 lines, bind a pipeline made with
+    // PrimitiveTopology::LineList and OccludingPlane::per_vertex before this
+    #[cfg(debug_assertions)]
+    pub fn draw_debug(&self, info: &mut DrawInfo)
+    {
+        self.assert_updated(&info.object_info);
+
+        info.object_info.builder_wrapper.builder()
+            .bind_vertex_buffers(0, self.debug_subbuffer.clone())
+            .unwrap()
+            .draw(DEBUG_VERTICES as u32, 1, 0, 0)
+            .unwrap();
+    }
+
     impl_updated_check!{}
 
     pub fn per_vertex() -> VertexBufferDescription
@@ -167,6 +307,14 @@ impl OccludingPlane
     }
 }
 
+impl ShadowCaster for OccludingPlane
+{
+    fn shadow_points(&self, origin: Vector3<f32>) -> Option<OccluderPoints>
+    {
+        Some(self.points(origin))
+    }
+}
+
 impl OnTransformCallback for OccludingPlane
 {
     fn callback(&mut self)
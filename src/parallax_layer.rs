@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use nalgebra::Vector2;
+
+use crate::{
+    Object,
+    ObjectFactory,
+    ObjectInfo,
+    camera::Camera,
+    game_object::*,
+    transform::{Transform, TransformContainer},
+    object::{Model, Texture}
+};
+
+
+#[derive(Debug)]
+pub struct ParallaxLayer
+{
+    object: Object,
+    factor: f32,
+    tiles: Vector2<f32>
+}
+
+impl ParallaxLayer
+{
+    // the texture should be created with Texture::repeating for the tiling to work
+    pub fn new(
+        object_factory: &ObjectFactory,
+        texture: Arc<RwLock<Texture>>,
+        factor: f32,
+        tiles: Vector2<f32>,
+        transform: Transform
+    ) -> Self
+    {
+        let object = object_factory.create(ObjectInfo{
+            model: Arc::new(RwLock::new(Model::square(1.0))),
+            texture,
+            transform
+        });
+
+        Self{object, factor, tiles}
+    }
+
+    pub fn factor(&self) -> f32
+    {
+        self.factor
+    }
+
+    pub fn set_factor(&mut self, factor: f32)
+    {
+        self.factor = factor;
+    }
+
+    pub fn update(&mut self, camera: &Camera)
+    {
+        let camera_position = camera.position().coords;
+
+        let mut position = *self.object.position();
+        position.x = camera_position.x;
+        position.y = camera_position.y;
+
+        self.object.set_position(position);
+
+        let tile_size = self.object.scale().xy().component_div(&self.tiles);
+        let offset = (camera_position.xy() * self.factor).component_div(&tile_size);
+
+        let mut model = Model::square(1.0);
+        model.uvs.iter_mut().for_each(|uv|
+        {
+            uv[0] = uv[0] * self.tiles.x + offset.x;
+            uv[1] = uv[1] * self.tiles.y + offset.y;
+        });
+
+        self.object.set_inplace_model_same_sized(model);
+    }
+}
+
+impl GameObject for ParallaxLayer
+{
+    fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        self.object.update_buffers(info);
+    }
+
+    fn draw(&self, info: &mut DrawInfo)
+    {
+        self.object.draw(info);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    fmt,
+    rc::Rc,
+    sync::Arc
+};
+
+use parking_lot::RwLock;
+
+use vulkano::{
+    format::{Format, ClearValue},
+    render_pass::Framebuffer,
+    command_buffer::{RenderPassBeginInfo, SubpassBeginInfo, SubpassContents},
+    pipeline::graphics::viewport::Viewport,
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    image::{
+        Image,
+        ImageType,
+        ImageUsage,
+        ImageCreateInfo,
+        SampleCount,
+        view::ImageView
+    }
+};
+
+use crate::{
+    UniformLocation,
+    ShaderId,
+    game_object::DrawInfo,
+    object::{Texture, resource_uploader::ResourceUploader}
+};
+
+
+#[derive(Debug, Clone)]
+pub struct RenderTargetImages
+{
+    pub color: Arc<ImageView>,
+    pub resolve: Option<Arc<ImageView>>
+}
+
+impl RenderTargetImages
+{
+    pub fn new(
+        allocator: Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+        format: Format,
+        samples: SampleCount
+    ) -> Self
+    {
+        let create_image = |samples, usage|
+        {
+            let image = Image::new(
+                allocator.clone(),
+                ImageCreateInfo{
+                    image_type: ImageType::Dim2d,
+                    format,
+                    extent: [extent[0], extent[1], 1],
+                    samples,
+                    usage,
+                    ..Default::default()
+                },
+                AllocationCreateInfo::default()
+            ).unwrap();
+
+            ImageView::new_default(image).unwrap()
+        };
+
+        if samples == SampleCount::Sample1
+        {
+            let color = create_image(samples, ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED);
+
+            Self{color, resolve: None}
+        } else
+        {
+            let color = create_image(
+                samples,
+                ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSIENT_ATTACHMENT
+            );
+
+            let resolve = create_image(
+                SampleCount::Sample1,
+                ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED
+            );
+
+            Self{color, resolve: Some(resolve)}
+        }
+    }
+
+    pub fn is_multisampled(&self) -> bool
+    {
+        self.resolve.is_some()
+    }
+
+    // the image that can actually be sampled after rendering
+    pub fn sampled(&self) -> &Arc<ImageView>
+    {
+        self.resolve.as_ref().unwrap_or(&self.color)
+    }
+
+    pub fn texture(
+        &self,
+        resource_uploader: &ResourceUploader,
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Texture
+    {
+        Texture::from_view(resource_uploader, self.sampled().clone(), location, shader)
+    }
+}
+
+pub type OffscreenCreateFn = Rc<dyn Fn([u32; 2]) -> (Arc<ImageView>, Arc<Framebuffer>)>;
+
+// makes framebuffers compatible with the main render pass so the same pipelines can draw into them
+#[derive(Clone)]
+pub struct OffscreenCreator
+{
+    pub(crate) create: OffscreenCreateFn,
+    pub(crate) clear_values: Vec<Option<ClearValue>>
+}
+
+impl fmt::Debug for OffscreenCreator
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("OffscreenCreator")
+            .field("clear_values", &self.clear_values)
+            .finish()
+    }
+}
+
+// draw into it between begin and end inside of YanyaApp::draw_offscreen,
+// then the texture can be used by objects in the main pass
+#[derive(Debug)]
+pub struct OffscreenTarget
+{
+    framebuffer: Arc<Framebuffer>,
+    texture: Arc<RwLock<Texture>>,
+    clear_values: Vec<Option<ClearValue>>
+}
+
+impl OffscreenTarget
+{
+    pub fn new(
+        resource_uploader: &ResourceUploader,
+        creator: &OffscreenCreator,
+        extent: [u32; 2],
+        location: UniformLocation,
+        shader: ShaderId
+    ) -> Self
+    {
+        let (view, framebuffer) = (creator.create)(extent);
+
+        let texture = Texture::from_view(resource_uploader, view, location, shader);
+
+        Self{
+            framebuffer,
+            texture: Arc::new(RwLock::new(texture)),
+            clear_values: creator.clear_values.clone()
+        }
+    }
+
+    pub fn texture(&self) -> &Arc<RwLock<Texture>>
+    {
+        &self.texture
+    }
+
+    pub fn extent(&self) -> [u32; 2]
+    {
+        self.framebuffer.extent()
+    }
+
+    pub fn set_clear_color(&mut self, color: [f32; 4])
+    {
+        self.clear_values[0] = Some(ClearValue::Float(color));
+    }
+
+    pub fn begin(&self, info: &mut DrawInfo)
+    {
+        let [width, height] = self.extent();
+
+        info.push_framebuffer(self.framebuffer.clone(), SubpassContents::Inline);
+
+        info.object_info.builder_wrapper.builder()
+            .begin_render_pass(
+                RenderPassBeginInfo{
+                    clear_values: self.clear_values.clone(),
+                    ..RenderPassBeginInfo::framebuffer(self.framebuffer.clone())
+                },
+                SubpassBeginInfo{
+                    contents: SubpassContents::Inline,
+                    ..Default::default()
+                }
+            )
+            .unwrap()
+            .set_viewport(0, [Viewport{
+                offset: [0.0, 0.0],
+                extent: [width as f32, height as f32],
+                depth_range: 0.0..=1.0
+            }].into_iter().collect())
+            .unwrap();
+    }
+
+    pub fn end(&self, info: &mut DrawInfo)
+    {
+        let extent = info.object_info.size;
+
+        info.pop_framebuffer();
+
+        info.object_info.builder_wrapper.builder()
+            .end_render_pass(Default::default())
+            .unwrap()
+            .set_viewport(0, [Viewport{
+                offset: [0.0, 0.0],
+                extent,
+                depth_range: 0.0..=1.0
+            }].into_iter().collect())
+            .unwrap();
+    }
+}
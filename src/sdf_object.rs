@@ -0,0 +1,215 @@
+use std::fmt;
+
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    pipeline::graphics::vertex_input::VertexBufferDescription
+};
+
+use nalgebra::{Vector3, Vector4, Matrix4};
+
+use crate::{
+    game_object::*,
+    object::{impl_updated_check, NormalGraphicalObject, ObjectTransform, ObjectVertex, Model},
+    allocators::{ObjectAllocator, FrameSubbuffers},
+    transform::{Transform, OnTransformCallback, TransformContainer}
+};
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct SdfParams
+{
+    pub color: [f32; 4],
+    pub radius: f32,
+    pub softness: f32
+}
+
+impl Default for SdfParams
+{
+    fn default() -> Self
+    {
+        Self{
+            color: [1.0; 4],
+            radius: 0.0,
+            softness: 0.0
+        }
+    }
+}
+
+impl SdfParams
+{
+    pub fn circle(color: [f32; 4]) -> Self
+    {
+        Self{
+            color,
+            radius: f32::MAX,
+            ..Default::default()
+        }
+    }
+
+    pub fn rounded_rectangle(color: [f32; 4], radius: f32) -> Self
+    {
+        Self{
+            color,
+            radius,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct SdfConstants
+{
+    color: [f32; 4],
+    size: [f32; 2],
+    radius: f32,
+    softness: f32
+}
+
+pub struct SdfObject
+{
+    pub params: SdfParams,
+    transform: ObjectTransform,
+    subbuffers: FrameSubbuffers<ObjectVertex>,
+    #[cfg(debug_assertions)]
+    updated_buffers: bool
+}
+
+impl NormalGraphicalObject<ObjectVertex> for SdfObject
+{
+    fn subbuffer(&self, frame_parity: bool) -> Subbuffer<[ObjectVertex]>
+    {
+        self.subbuffers[frame_parity as usize].clone()
+    }
+
+    fn vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
+    {
+        self.calculate_vertices(projection_view)
+    }
+
+    impl_updated_check!{}
+}
+
+impl SdfObject
+{
+    pub fn new_default(
+        params: SdfParams,
+        allocator: &ObjectAllocator
+    ) -> Self
+    {
+        let transform = ObjectTransform::new_default();
+
+        Self::new(params, transform, allocator)
+    }
+
+    pub fn new(
+        params: SdfParams,
+        transform: ObjectTransform,
+        allocator: &ObjectAllocator
+    ) -> Self
+    {
+        let subbuffers = allocator.frame_subbuffers(Model::square(1.0).vertices.len() as u64);
+
+        Self{
+            params,
+            transform,
+            subbuffers,
+            #[cfg(debug_assertions)]
+            updated_buffers: false
+        }
+    }
+
+    fn calculate_vertices(&self, projection_view: Matrix4<f32>) -> Box<[ObjectVertex]>
+    {
+        let transform = self.transform.matrix();
+
+        let model = Model::square(1.0);
+
+        model.vertices.iter().zip(model.uvs.iter()).map(move |(vertex, uv)|
+        {
+            let vertex = Vector4::new(vertex[0], vertex[1], vertex[2], 1.0);
+
+            let vertex = projection_view * transform * vertex;
+
+            ObjectVertex{position: vertex.into(), uv: *uv}
+        }).collect::<Box<[_]>>()
+    }
+
+    fn constants(&self) -> SdfConstants
+    {
+        let scale = self.transform.transform_ref().scale;
+
+        SdfConstants{
+            color: self.params.color,
+            size: [scale.x, scale.y],
+            radius: self.params.radius,
+            softness: self.params.softness
+        }
+    }
+
+    pub fn set_origin(&mut self, origin: Vector3<f32>)
+    {
+        self.transform.set_origin(origin);
+    }
+
+    pub fn per_vertex() -> VertexBufferDescription
+    {
+        crate::Object::per_vertex()
+    }
+}
+
+impl GameObject for SdfObject
+{
+    fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        self.normal_update_buffers(info);
+    }
+
+    fn draw(&self, info: &mut DrawInfo)
+    {
+        self.assert_updated(&info.object_info);
+
+        info.push_constants(self.constants());
+
+        let subbuffer = self.subbuffer(info.object_info.frame_parity);
+        let size = subbuffer.len() as u32;
+
+        info.object_info.builder_wrapper.builder()
+            .bind_vertex_buffers(0, subbuffer)
+            .unwrap()
+            .draw(size, 1, 0, 0)
+            .unwrap();
+    }
+}
+
+impl OnTransformCallback for SdfObject
+{
+    fn callback(&mut self)
+    {
+        self.transform.callback();
+    }
+}
+
+impl TransformContainer for SdfObject
+{
+    fn transform_ref(&self) -> &Transform
+    {
+        self.transform.transform_ref()
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform
+    {
+        self.transform.transform_mut()
+    }
+}
+
+impl fmt::Debug for SdfObject
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("SdfObject")
+            .field("params", &self.params)
+            .field("transform", &self.transform)
+            .finish()
+    }
+}
@@ -1,6 +1,7 @@
 #[allow(unused_imports)]
 use std::{
     fmt,
+    mem,
     cell::RefCell,
     sync::Arc
 };
@@ -18,7 +19,7 @@ use crate::{
     game_object::*,
     SimpleVertex,
     object::{impl_updated_check, NormalGraphicalObject, ObjectTransform, Model},
-    allocators::ObjectAllocator,
+    allocators::{ObjectAllocator, FrameSubbuffers},
     transform::{Transform, OnTransformCallback, TransformContainer}
 };
 
@@ -27,16 +28,16 @@ pub struct SolidObject
 {
     model: Arc<RwLock<Model>>,
     transform: ObjectTransform,
-    subbuffer: Subbuffer<[SimpleVertex]>,
+    subbuffers: FrameSubbuffers<SimpleVertex>,
     #[cfg(debug_assertions)]
     updated_buffers: bool
 }
 
 impl NormalGraphicalObject<SimpleVertex> for SolidObject
 {
-    fn subbuffer(&self) -> Subbuffer<[SimpleVertex]>
+    fn subbuffer(&self, frame_parity: bool) -> Subbuffer<[SimpleVertex]>
     {
-        self.subbuffer.clone()
+        self.subbuffers[frame_parity as usize].clone()
     }
 
     fn vertices(&self, projection_view: Matrix4<f32>) -> Box<[SimpleVertex]>
@@ -66,12 +67,12 @@ impl SolidObject
         allocator: &ObjectAllocator
     ) -> Self
     {
-        let subbuffer = allocator.subbuffer(model.read().vertices.len() as u64);
+        let subbuffers = allocator.frame_subbuffers(model.read().vertices.len() as u64);
 
         Self{
             model,
             transform,
-            subbuffer,
+            subbuffers,
             #[cfg(debug_assertions)]
             updated_buffers: false
         }
@@ -98,6 +99,25 @@ impl SolidObject
         self.transform.set_origin(origin);
     }
 
+    pub fn set_pivot(&mut self, pivot: Vector3<f32>)
+    {
+        self.transform.set_pivot(pivot);
+    }
+
+    // the model can be swapped for one with more vertices (like when its done loading)
+    fn fit_model(&mut self, allocator: &ObjectAllocator)
+    {
+        let size = self.model.read().vertices.len() as u64;
+        if size > self.subbuffers[0].len()
+        {
+            let subbuffers = allocator.frame_subbuffers(size);
+            mem::replace(&mut self.subbuffers, subbuffers).into_iter().for_each(|subbuffer|
+            {
+                allocator.recycle(subbuffer);
+            });
+        }
+    }
+
     fn needs_draw(&self) -> bool
     {
         !self.model.read().vertices.is_empty()
@@ -113,6 +133,9 @@ impl GameObject for SolidObject
 {
     fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
     {
+        let object_factory = info.partial.object_factory.clone();
+        self.fit_model(object_factory.allocator());
+
         self.normal_update_buffers(info);
     }
 
@@ -128,7 +151,7 @@ impl GameObject for SolidObject
         let size = self.model.read().vertices.len() as u32;
 
         info.object_info.builder_wrapper.builder()
-            .bind_vertex_buffers(0, self.subbuffer.clone())
+            .bind_vertex_buffers(0, self.subbuffer(info.object_info.frame_parity))
             .unwrap()
             .draw(size, 1, 0, 0)
             .unwrap();
@@ -0,0 +1,280 @@
+use std::{
+    fmt,
+    mem,
+    ops::Range,
+    sync::Arc
+};
+
+use parking_lot::RwLock;
+
+use vulkano::{
+    pipeline::{
+        PipelineBindPoint,
+        graphics::vertex_input::VertexBufferDescription
+    }
+};
+
+use nalgebra::Matrix4;
+
+use crate::{
+    Object,
+    game_object::*,
+    object::{impl_updated_check, ObjectVertex, Model, Texture},
+    allocators::{ObjectAllocator, FrameSubbuffers}
+};
+
+
+pub(crate) struct Sprite
+{
+    pub(crate) model: Arc<RwLock<Model>>,
+    pub(crate) texture: Arc<RwLock<Texture>>,
+    pub(crate) transform: Matrix4<f32>,
+    pub(crate) layer: Option<u8>
+}
+
+// merges the pushed objects into one vertex and index buffer, consecutive objects
+// with the same texture get drawn with a single draw call, so sort them by texture
+// for the best results (clips of the objects are ignored)
+pub struct SpriteBatch
+{
+    sprites: Vec<Sprite>,
+    runs: Vec<(Arc<RwLock<Texture>>, Range<u32>)>,
+    growth: f32,
+    vertices: FrameSubbuffers<ObjectVertex>,
+    indices: FrameSubbuffers<u32>,
+    #[cfg(debug_assertions)]
+    updated_buffers: bool
+}
+
+impl SpriteBatch
+{
+    pub fn new(capacity: usize, allocator: &ObjectAllocator) -> Self
+    {
+        // quads are the common case, 4 unique corners and 6 indices
+        let capacity = capacity.max(1) as u64;
+
+        Self{
+            sprites: Vec::new(),
+            runs: Vec::new(),
+            growth: 2.0,
+            vertices: allocator.frame_subbuffers(capacity * 4),
+            indices: allocator.frame_subbuffers(capacity * 6),
+            #[cfg(debug_assertions)]
+            updated_buffers: false
+        }
+    }
+
+    pub fn push(&mut self, object: &Object)
+    {
+        self.sprites.push(object.sprite());
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.sprites.clear();
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.sprites.is_empty()
+    }
+
+    // how many draw calls the last update_buffers ended up with
+    pub fn draw_calls(&self) -> usize
+    {
+        self.runs.len()
+    }
+
+    // how much the buffers get multiplied by when the sprites dont fit
+    pub fn set_growth(&mut self, growth: f32)
+    {
+        self.growth = growth.max(1.0);
+    }
+
+    // which model vertices are unique and which of those every vertex uses, shared corners
+    // (like the ones between the 2 triangles of a quad) only get stored once
+    fn unique_vertices(model: &Model) -> (Vec<usize>, Vec<u32>)
+    {
+        let mut seen = ahash::HashMap::default();
+        let mut unique = Vec::new();
+
+        let indices = model.vertices.iter().zip(model.uvs.iter()).enumerate().map(|(index, (vertex, uv))|
+        {
+            let key = (vertex.map(f32::to_bits), uv.map(f32::to_bits));
+
+            *seen.entry(key).or_insert_with(||
+            {
+                unique.push(index);
+
+                unique.len() as u32 - 1
+            })
+        }).collect();
+
+        (unique, indices)
+    }
+
+    fn calculate_buffers(&mut self, projection_view: Matrix4<f32>) -> (Vec<ObjectVertex>, Vec<u32>)
+    {
+        let mut vertices: Vec<ObjectVertex> = Vec::new();
+        let mut indices = Vec::new();
+
+        // sprites usually share a few models so this only gets done once for each
+        let mut models_unique = ahash::HashMap::default();
+
+        self.runs.clear();
+
+        self.sprites.iter().for_each(|sprite|
+        {
+            let model = sprite.model.read();
+
+            let (unique, model_indices) = models_unique.entry(Arc::as_ptr(&sprite.model))
+                .or_insert_with(|| Self::unique_vertices(&model));
+
+            let base = vertices.len() as u32;
+            let start = indices.len() as u32;
+
+            let transformed: Vec<_> = Object::transformed_vertices(
+                &model,
+                sprite.transform,
+                sprite.layer,
+                projection_view
+            ).collect();
+
+            vertices.extend(unique.iter().map(|index| transformed[*index]));
+            indices.extend(model_indices.iter().map(|index| base + index));
+
+            let end = indices.len() as u32;
+
+            match self.runs.last_mut()
+            {
+                Some((texture, range)) if Arc::ptr_eq(texture, &sprite.texture) =>
+                {
+                    range.end = end;
+                },
+                _ => self.runs.push((sprite.texture.clone(), start..end))
+            }
+        });
+
+        (vertices, indices)
+    }
+
+    fn reserve(&mut self, allocator: &ObjectAllocator, vertices: usize, indices: usize)
+    {
+        let capacity = self.vertices[0].len() as usize;
+        if vertices > capacity
+        {
+            let grown = (capacity as f32 * self.growth).ceil() as usize;
+
+            let subbuffers = allocator.frame_subbuffers(grown.max(vertices) as u64);
+            mem::replace(&mut self.vertices, subbuffers).into_iter().for_each(|subbuffer|
+            {
+                allocator.recycle(subbuffer);
+            });
+        }
+
+        let capacity = self.indices[0].len() as usize;
+        if indices > capacity
+        {
+            let grown = (capacity as f32 * self.growth).ceil() as usize;
+
+            let subbuffers = allocator.frame_subbuffers(grown.max(indices) as u64);
+            mem::replace(&mut self.indices, subbuffers).into_iter().for_each(|subbuffer|
+            {
+                allocator.recycle(subbuffer);
+            });
+        }
+    }
+
+    pub fn per_vertex() -> VertexBufferDescription
+    {
+        Object::per_vertex()
+    }
+
+    impl_updated_check!{}
+}
+
+impl GameObject for SpriteBatch
+{
+    fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let (vertices, indices) = self.calculate_buffers(info.projection_view);
+
+        self.set_updated(&info.partial);
+
+        if indices.is_empty()
+        {
+            // sprites with empty models still leave their runs behind
+            self.runs.clear();
+
+            return;
+        }
+
+        let object_factory = info.partial.object_factory.clone();
+        self.reserve(object_factory.allocator(), vertices.len(), indices.len());
+
+        let parity = info.partial.frame_parity as usize;
+
+        let builder_wrapper = &mut info.partial.builder_wrapper;
+
+        builder_wrapper.update_buffer(&self.vertices[parity], &vertices);
+        builder_wrapper.update_buffer(&self.indices[parity], &indices);
+    }
+
+    fn draw(&self, info: &mut DrawInfo)
+    {
+        if self.runs.is_empty()
+        {
+            return;
+        }
+
+        self.assert_updated(&info.object_info);
+
+        let parity = info.object_info.frame_parity as usize;
+
+        let layout = info.current_layout();
+        let builder = info.object_info.builder_wrapper.builder();
+
+        builder
+            .bind_vertex_buffers(0, self.vertices[parity].clone())
+            .unwrap()
+            .bind_index_buffer(self.indices[parity].clone())
+            .unwrap();
+
+        // a new draw call only when the texture changes
+        self.runs.iter().for_each(|(texture, range)|
+        {
+            let (set, descriptor_set) = {
+                let texture = texture.read();
+
+                (texture.location().set, texture.descriptor_set())
+            };
+
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    layout.clone(),
+                    set,
+                    descriptor_set
+                )
+                .unwrap()
+                .draw_indexed(range.len() as u32, 1, range.start, 0, 0)
+                .unwrap();
+        });
+    }
+}
+
+impl fmt::Debug for SpriteBatch
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("SpriteBatch")
+            .field("sprites", &self.sprites.len())
+            .field("draw_calls", &self.runs.len())
+            .finish()
+    }
+}
@@ -1,7 +1,17 @@
-use std::rc::Rc;
+use std::{
+    io,
+    rc::Rc,
+    path::Path,
+    sync::Arc,
+    collections::{HashMap, HashSet},
+    cell::{Cell, RefCell}
+};
+
+use parking_lot::RwLock;
 
 use font_kit::{
     font::Font,
+    error::FontLoadingError,
     source::SystemSource,
     properties::{Properties, Weight},
     family_name::FamilyName
@@ -15,26 +25,126 @@ use crate::{
     ObjectFactory,
     TextObject,
     TextAlign,
+    HorizontalAlign,
     UniformLocation,
     ShaderId,
     text_object::CharsRasterizer,
-    object::resource_uploader::ResourceUploader
+    object::{Texture, resource_uploader::ResourceUploader}
 };
 
 pub use crate::text_object::TextCreateInfo;
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FontStyle
 {
-    Sans = 0,
+    Sans,
     Serif,
-    Bold
+    Bold,
+    // any font in the container by its index, for fonts loaded with from_paths
+    Custom(usize)
+}
+
+impl FontStyle
+{
+    pub fn index(self) -> usize
+    {
+        match self
+        {
+            Self::Sans => 0,
+            Self::Serif => 1,
+            Self::Bold => 2,
+            Self::Custom(index) => index
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextCacheKey
+{
+    blocks: Vec<([u8; 3], String)>,
+    font: FontStyle,
+    font_size: u32,
+    outline: Option<TextOutline>,
+    max_width: Option<u32>,
+    align: HorizontalAlign,
+    location: UniformLocation,
+    shader: ShaderId
+}
+
+impl TextCacheKey
+{
+    pub fn new(info: &TextInfo, location: UniformLocation, shader: ShaderId) -> Self
+    {
+        Self{
+            blocks: info.text.0.iter().map(|block| (block.color, block.text.to_owned())).collect(),
+            font: info.font,
+            font_size: info.font_size,
+            outline: info.outline,
+            max_width: info.max_width.map(f32::to_bits),
+            align: info.align.horizontal,
+            location,
+            shader
+        }
+    }
+}
+
+// least recently used textures get dropped when its full
+struct TextCache
+{
+    textures: HashMap<TextCacheKey, (Arc<RwLock<Texture>>, u64)>,
+    capacity: usize,
+    tick: u64
+}
+
+impl TextCache
+{
+    fn new() -> Self
+    {
+        Self{textures: HashMap::new(), capacity: 256, tick: 0}
+    }
+
+    fn get(&mut self, key: &TextCacheKey) -> Option<Arc<RwLock<Texture>>>
+    {
+        self.tick += 1;
+
+        let (texture, last_used) = self.textures.get_mut(key)?;
+        *last_used = self.tick;
+
+        Some(texture.clone())
+    }
+
+    fn insert(&mut self, key: TextCacheKey, texture: Arc<RwLock<Texture>>)
+    {
+        self.tick += 1;
+
+        self.textures.insert(key, (texture, self.tick));
+
+        self.evict();
+    }
+
+    fn evict(&mut self)
+    {
+        while self.textures.len() > self.capacity
+        {
+            let oldest = self.textures.iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest
+            {
+                self.textures.remove(&oldest);
+            }
+        }
+    }
 }
 
 pub struct FontsContainer
 {
-    font_textures: Vec<CharsRasterizer>
+    font_textures: Vec<CharsRasterizer>,
+    coverage_gamma: Cell<f32>,
+    text_cache: RefCell<TextCache>,
+    warned_fonts: RefCell<HashSet<FontStyle>>
 }
 
 impl FontsContainer
@@ -59,6 +169,47 @@ impl FontsContainer
         Self::from_fonts(fonts.into_iter())
     }
 
+    pub fn from_paths(paths: &[impl AsRef<Path>]) -> Self
+    {
+        Self::try_from_paths(paths).unwrap_or_else(|err|
+        {
+            panic!("couldnt load fonts: {err}")
+        })
+    }
+
+    pub fn try_from_paths(paths: &[impl AsRef<Path>]) -> Result<Self, io::Error>
+    {
+        let fonts = paths.iter().map(|path|
+        {
+            Font::from_path(path, 0).map_err(|err|
+            {
+                match err
+                {
+                    FontLoadingError::Io(err) => err,
+                    err => io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {err}", path.as_ref().display())
+                    )
+                }
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::from_fonts(fonts.into_iter()))
+    }
+
+    pub fn from_bytes(fonts: Vec<Vec<u8>>) -> Self
+    {
+        let fonts = fonts.into_iter().enumerate().map(|(index, bytes)|
+        {
+            Font::from_bytes(Arc::new(bytes), 0).unwrap_or_else(|err|
+            {
+                panic!("couldnt load font #{index}: {err}")
+            })
+        });
+
+        Self::from_fonts(fonts)
+    }
+
     fn from_fonts(fonts: impl Iterator<Item=Font>) -> Self
     {
         let font_textures = fonts.map(|font|
@@ -66,7 +217,57 @@ impl FontsContainer
             CharsRasterizer::new(font)
         }).collect();
 
-        Self{font_textures}
+        Self{
+            font_textures,
+            coverage_gamma: Cell::new(1.0),
+            text_cache: RefCell::new(TextCache::new()),
+            warned_fonts: RefCell::new(HashSet::new())
+        }
+    }
+
+    pub fn cached_text(&self, key: &TextCacheKey) -> Option<Arc<RwLock<Texture>>>
+    {
+        self.text_cache.borrow_mut().get(key)
+    }
+
+    pub fn cache_text(&self, key: TextCacheKey, texture: Arc<RwLock<Texture>>)
+    {
+        self.text_cache.borrow_mut().insert(key, texture);
+    }
+
+    // how many different texts stay cached, 256 by default
+    pub fn set_text_cache_capacity(&self, capacity: usize)
+    {
+        let mut text_cache = self.text_cache.borrow_mut();
+
+        text_cache.capacity = capacity;
+        text_cache.evict();
+    }
+
+    pub fn clear_text_cache(&self)
+    {
+        self.text_cache.borrow_mut().textures.clear();
+    }
+
+    pub fn swap_pipelines(&self, resource_uploader: &ResourceUploader)
+    {
+        self.text_cache.borrow().textures.values().for_each(|(texture, _)|
+        {
+            texture.write().swap_pipeline(resource_uploader);
+        });
+    }
+
+    pub fn coverage_gamma(&self) -> f32
+    {
+        self.coverage_gamma.get()
+    }
+
+    pub fn set_coverage_gamma(&self, gamma: f32)
+    {
+        self.coverage_gamma.set(gamma);
+
+        // the cached textures were rasterized with the old gamma
+        self.clear_text_cache();
     }
 
     pub fn calculate_bounds(&self, info: TextInfo) -> Vector2<f32>
@@ -86,7 +287,95 @@ impl FontsContainer
 
     pub fn get(&self, font: FontStyle) -> Option<&CharsRasterizer>
     {
-        self.font_textures.get(font as usize)
+        self.font_textures.get(font.index())
+    }
+
+    // falls back to the first font (warning once per missing font), none if there are no fonts at all
+    pub fn get_or_default(&self, font: FontStyle) -> Option<&CharsRasterizer>
+    {
+        self.get(font).or_else(||
+        {
+            if self.warned_fonts.borrow_mut().insert(font)
+            {
+                eprintln!("font {font:?} doesnt exist, using the first font");
+            }
+
+            self.font_textures.first()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TextOutline
+{
+    pub color: [u8; 3],
+    pub size: u32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextInfoBlock<'a>
+{
+    pub color: [u8; 3],
+    pub text: &'a str
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TextBlocks<'a>(pub Vec<TextInfoBlock<'a>>);
+
+impl<'a> TextBlocks<'a>
+{
+    pub fn single(color: [u8; 3], text: &'a str) -> Self
+    {
+        Self(vec![TextInfoBlock{color, text}])
+    }
+
+    pub fn push(&mut self, color: [u8; 3], text: &'a str)
+    {
+        self.0.push(TextInfoBlock{color, text});
+    }
+
+    // every character with the color of its block, split on newlines
+    pub fn lines(&self) -> Vec<Vec<(char, [u8; 3])>>
+    {
+        let mut lines = vec![Vec::new()];
+
+        self.0.iter().for_each(|block|
+        {
+            block.text.chars().for_each(|c|
+            {
+                if c == '\n'
+                {
+                    lines.push(Vec::new());
+                } else
+                {
+                    lines.last_mut().unwrap().push((c, block.color));
+                }
+            });
+        });
+
+        lines.iter_mut().for_each(|line|
+        {
+            if line.last().map(|(c, _)| *c == '\r').unwrap_or(false)
+            {
+                line.pop();
+            }
+        });
+
+        // same as str::lines, a trailing newline doesnt start a new line
+        if lines.last().map(|line| line.is_empty()).unwrap_or(false)
+        {
+            lines.pop();
+        }
+
+        lines
+    }
+}
+
+impl<'a> From<&'a str> for TextBlocks<'a>
+{
+    fn from(text: &'a str) -> Self
+    {
+        Self::single([u8::MAX; 3], text)
     }
 }
 
@@ -95,7 +384,10 @@ pub struct TextInfo<'a>
     pub font_size: u32,
     pub font: FontStyle,
     pub align: TextAlign,
-    pub text: &'a str
+    pub outline: Option<TextOutline>,
+    // in the same units as the bounds, wraps between words if exceeded
+    pub max_width: Option<f32>,
+    pub text: TextBlocks<'a>
 }
 
 pub struct TextFactory<'a, 'b: 'a>
@@ -1,5 +1,8 @@
 use std::{
+    rc::Rc,
+    cell::RefCell,
     num::FpCategory,
+    collections::HashMap,
     sync::Arc
 };
 
@@ -26,6 +29,7 @@ use crate::{
     ObjectFactory,
     TextInfo,
     FontsContainer,
+    text_factory::TextCacheKey,
     ObjectInfo,
     UniformLocation,
     ShaderId,
@@ -34,7 +38,7 @@ use crate::{
     object::{
         resource_uploader::ResourceUploader,
         model::Model,
-        texture::{Texture, Color, SimpleImage}
+        texture::{Texture, Color, SimpleImage, ImageOutline}
     }
 };
 
@@ -53,6 +57,7 @@ struct BoundsInfo
 struct BoundsCalculator
 {
     width: i32,
+    line_width: i32,
     x: i32,
     y: u32
 }
@@ -63,6 +68,7 @@ impl BoundsCalculator
     {
         Self{
             width: 0,
+            line_width: 0,
             x: 0,
             y: 0
         }
@@ -70,7 +76,10 @@ impl BoundsCalculator
 
     pub fn process_character(&mut self, info: BoundsInfo) -> i32
     {
-        self.width = self.width.max(self.x + info.origin.x + info.width as i32);
+        let right = self.x + info.origin.x + info.width as i32;
+
+        self.width = self.width.max(right);
+        self.line_width = self.line_width.max(right);
 
         let this_x = self.x + info.origin.x;
 
@@ -82,6 +91,7 @@ impl BoundsCalculator
     pub fn return_carriage(&mut self)
     {
         self.x = 0;
+        self.line_width = 0;
     }
 }
 
@@ -93,7 +103,7 @@ pub struct GlyphInfo
     pub height: u32
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HorizontalAlign
 {
     Left,
@@ -112,6 +122,19 @@ impl HorizontalAlign
             Self::Right => 1.0
         }
     }
+
+    // how far a line should be shifted inside of the full text width
+    pub fn line_offset(self, width: i32, line_width: i32) -> i32
+    {
+        let space = width - line_width;
+
+        match self
+        {
+            Self::Left => 0,
+            Self::Middle => space / 2,
+            Self::Right => space
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -164,6 +187,80 @@ impl TextAlign
     }
 }
 
+// positions are relative to the top left corner of the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRect
+{
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout
+{
+    pub size: Vector2<f32>,
+    pub lines: Vec<LineRect>
+}
+
+type ColoredChar = (char, [u8; 3]);
+
+#[derive(Debug, Clone, Copy)]
+pub struct CharInfo
+{
+    pub x: i32,
+    pub line: usize,
+    pub c: char,
+    pub color: [u8; 3]
+}
+
+struct ProcessedText
+{
+    chars_info: Vec<CharInfo>,
+    size: Vector2<i32>,
+    height_single: i32,
+    line_widths: Vec<i32>,
+    line_offsets: Vec<i32>,
+    // the outline grows the texture on every side
+    padding: i32
+}
+
+impl ProcessedText
+{
+    fn empty() -> Self
+    {
+        Self{
+            chars_info: Vec::new(),
+            size: Vector2::zeros(),
+            height_single: 0,
+            line_widths: Vec::new(),
+            line_offsets: Vec::new(),
+            padding: 0
+        }
+    }
+
+    fn layout(&self) -> TextLayout
+    {
+        let height = TextObject::bounds_to_global(Vector2::new(0, self.height_single)).y;
+        let padding = TextObject::bounds_to_global(Vector2::repeat(self.padding));
+
+        let lines = self.line_widths.iter().zip(self.line_offsets.iter()).enumerate().map(|(index, (width, offset))|
+        {
+            let width = TextObject::bounds_to_global(Vector2::new(*width, 0)).x;
+            let offset = TextObject::bounds_to_global(Vector2::new(*offset, 0)).x;
+
+            LineRect{
+                position: Vector2::new(offset, height * index as f32) + padding,
+                size: Vector2::new(width, height)
+            }
+        }).collect();
+
+        TextLayout{
+            size: TextObject::bounds_to_global(self.size + Vector2::repeat(self.padding * 2)),
+            lines
+        }
+    }
+}
+
 pub struct TextCreateInfo<'a>
 {
     pub transform: Transform,
@@ -177,6 +274,7 @@ pub struct TextObject
     pub object: Option<Object>,
     align: TextAlign,
     dynamic_scale: Option<Vector2<f32>>,
+    layout: TextLayout,
     size: Vector2<f32>
 }
 
@@ -191,45 +289,80 @@ impl TextObject
         shader: ShaderId
     ) -> Self
     {
-        let current_font = fonts.get(info.inner.font).expect("style must exist");
+        let current_font = fonts.get_or_default(info.inner.font);
 
         let align = info.inner.align.clone();
         let font_size = info.inner.font_size;
+        let outline = info.inner.outline;
 
-        let (chars_info, size, height_single) = Self::calculate_bounds_pixels(info.inner, fonts);
+        let cache_key = TextCacheKey::new(&info.inner, location, shader);
 
-        let global_size = Self::bounds_to_global(size);
+        let processed = Self::process_text(info.inner, fonts);
 
-        if size.x == 0 || size.y == 0
-        {
-            return Self{
-                object: None,
-                align,
-                dynamic_scale: info.dynamic_scale,
-                size: global_size
-            };
-        }
+        let layout = processed.layout();
+        let ProcessedText{chars_info, size, height_single, ..} = processed;
 
-        let mut text_canvas = Canvas::new(
-            Vector2I::new(size.x, size.y),
-            Format::A8
-        );
+        let global_size = layout.size;
+
+        let current_font = match current_font
+        {
+            Some(font) if size.x != 0 && size.y != 0 => font,
+            _ =>
+            {
+                return Self{
+                    object: None,
+                    align,
+                    dynamic_scale: info.dynamic_scale,
+                    layout,
+                    size: global_size
+                };
+            }
+        };
 
-        chars_info.into_iter().for_each(|(x, y, c)|
+        let texture = fonts.cached_text(&cache_key).unwrap_or_else(||
         {
-            current_font.render_glyph(
-                &mut text_canvas,
-                height_single,
-                font_size,
-                x,
-                y,
-                c
+            let mut text_canvas = Canvas::new(
+                Vector2I::new(size.x, size.y),
+                Format::A8
             );
+
+            let mut colors = vec![[u8::MAX; 3]; (size.x * size.y) as usize];
+
+            chars_info.into_iter().for_each(|info|
+            {
+                current_font.render_glyph(
+                    &mut text_canvas,
+                    &mut colors,
+                    height_single,
+                    font_size,
+                    info
+                );
+            });
+
+            let image = Self::canvas_to_image(text_canvas, &colors, fonts.coverage_gamma());
+
+            let image = if let Some(outline) = outline
+            {
+                image.outlined(ImageOutline{
+                    color: Color::new(outline.color[0], outline.color[1], outline.color[2], u8::MAX),
+                    size: outline.size as f32
+                })
+            } else
+            {
+                image
+            };
+
+            let texture = Texture::new(resource_uploader, image.into(), location, shader);
+            let texture = Arc::new(RwLock::new(texture));
+
+            fonts.cache_text(cache_key, texture.clone());
+
+            texture
         });
 
         let object = object_factory.create(ObjectInfo{
             model: Arc::new(RwLock::new(Model::square(1.0))),
-            texture: Self::canvas_to_texture(resource_uploader, text_canvas, location, shader),
+            texture,
             transform: info.transform
         });
 
@@ -237,6 +370,7 @@ impl TextObject
             object: Some(object),
             align,
             dynamic_scale: info.dynamic_scale,
+            layout,
             size: global_size
         };
 
@@ -248,18 +382,50 @@ impl TextObject
     pub fn calculate_bounds_pixels(
         info: TextInfo,
         fonts: &FontsContainer
-    ) -> (Vec<(i32, usize, char)>, Vector2<i32>, i32)
+    ) -> (Vec<CharInfo>, Vector2<i32>, i32)
+    {
+        let ProcessedText{chars_info, size, height_single, ..} = Self::process_text(info, fonts);
+
+        (chars_info, size, height_single)
+    }
+
+    fn process_text(
+        info: TextInfo,
+        fonts: &FontsContainer
+    ) -> ProcessedText
     {
-        let current_font = fonts.get(info.font).expect("style must exist");
+        let current_font = if let Some(font) = fonts.get_or_default(info.font)
+        {
+            font
+        } else
+        {
+            return ProcessedText::empty();
+        };
 
         let mut full_bounds = BoundsCalculator::new();
+        let mut line_widths = Vec::new();
 
-        let lines_count = info.text.lines().count();
-        let chars_info: Vec<_> = info.text.lines().enumerate().flat_map(|(y, line)|
+        let lines = info.text.lines();
+
+        let lines = if let Some(max_width) = info.max_width
+        {
+            let max_width = Self::global_to_bounds(Vector2::new(max_width, 0.0)).x;
+
+            lines.into_iter().flat_map(|line|
+            {
+                Self::wrap_line(current_font, info.font_size, max_width, line)
+            }).collect()
+        } else
+        {
+            lines
+        };
+
+        let lines_count = lines.len();
+        let mut chars_info: Vec<_> = lines.into_iter().enumerate().flat_map(|(y, line)|
         {
             full_bounds.return_carriage();
             // i dunno how to not collect >_<
-            line.chars().into_iter().map(|c|
+            let line_chars = line.into_iter().map(|(c, color)|
             {
                 let x = Self::with_font(
                     current_font,
@@ -268,8 +434,12 @@ impl TextObject
                     c
                 ).0;
 
-                (x, y, c)
-            }).collect::<Vec<_>>()
+                CharInfo{x, line: y, c, color}
+            }).collect::<Vec<_>>();
+
+            line_widths.push(full_bounds.line_width);
+
+            line_chars
         }).collect();
 
         let metrics = current_font.metrics();
@@ -282,7 +452,25 @@ impl TextObject
         let height = height_single * lines_count as i32;
         let width = full_bounds.width;
 
-        (chars_info, Vector2::new(width, height), height_single)
+        // shorter lines get shifted over to match the alignment
+        let line_offsets: Vec<_> = line_widths.iter().map(|line_width|
+        {
+            info.align.horizontal.line_offset(width, *line_width)
+        }).collect();
+
+        chars_info.iter_mut().for_each(|char_info|
+        {
+            char_info.x += line_offsets[char_info.line];
+        });
+
+        ProcessedText{
+            chars_info,
+            size: Vector2::new(width, height),
+            height_single,
+            line_widths,
+            line_offsets,
+            padding: info.outline.map(|outline| outline.size as i32).unwrap_or(0)
+        }
     }
 
     pub fn bounds_to_global(bounds: Vector2<i32>) -> Vector2<f32>
@@ -294,12 +482,101 @@ impl TextObject
         v / 1920.0
     }
 
+    pub fn global_to_bounds(global: Vector2<f32>) -> Vector2<i32>
+    {
+        (global * 1920.0).map(|x| x.round() as i32)
+    }
+
+    fn wrap_line(
+        rasterizer: &CharsRasterizer,
+        font_size: u32,
+        max_width: i32,
+        line: Vec<ColoredChar>
+    ) -> Vec<Vec<ColoredChar>>
+    {
+        let advance = |c: char| (rasterizer.advance(c) * font_size as f32).round() as i32;
+        let group_width = |group: &[ColoredChar]| -> i32
+        {
+            group.iter().map(|(c, _)| advance(*c)).sum()
+        };
+
+        // split into alternating runs of whitespace and words
+        let mut groups: Vec<(bool, Vec<ColoredChar>)> = Vec::new();
+        line.into_iter().for_each(|(c, color)|
+        {
+            let is_space = c.is_whitespace();
+
+            match groups.last_mut()
+            {
+                Some((last_space, group)) if *last_space == is_space => group.push((c, color)),
+                _ => groups.push((is_space, vec![(c, color)]))
+            }
+        });
+
+        let mut lines = Vec::new();
+        let mut current = Vec::new();
+        let mut pending_space = Vec::new();
+        let mut pen = 0;
+
+        groups.into_iter().for_each(|(is_space, group)|
+        {
+            if is_space
+            {
+                // whitespace at the start of a wrapped line gets collapsed
+                if !current.is_empty() || lines.is_empty()
+                {
+                    pending_space = group;
+                }
+
+                return;
+            }
+
+            let space_width = group_width(&pending_space);
+            if !current.is_empty() && pen + space_width + group_width(&group) > max_width
+            {
+                lines.push(std::mem::take(&mut current));
+                pending_space.clear();
+                pen = 0;
+            } else
+            {
+                pen += space_width;
+                current.append(&mut pending_space);
+            }
+
+            // words that dont fit on their own line get broken anywhere
+            group.into_iter().for_each(|(c, color)|
+            {
+                let width = advance(c);
+                if !current.is_empty() && pen + width > max_width
+                {
+                    lines.push(std::mem::take(&mut current));
+                    pen = 0;
+                }
+
+                current.push((c, color));
+                pen += width;
+            });
+        });
+
+        lines.push(current);
+
+        lines
+    }
+
     pub fn calculate_bounds(
         info: TextInfo,
         fonts: &FontsContainer
     ) -> Vector2<f32>
     {
-        Self::bounds_to_global(Self::calculate_bounds_pixels(info, fonts).1)
+        Self::calculate_layout(info, fonts).size
+    }
+
+    pub fn calculate_layout(
+        info: TextInfo,
+        fonts: &FontsContainer
+    ) -> TextLayout
+    {
+        Self::process_text(info, fonts).layout()
     }
 
     pub fn text_size(&self) -> Vector2<f32>
@@ -307,6 +584,16 @@ impl TextObject
         self.size
     }
 
+    pub fn layout(&self) -> &TextLayout
+    {
+        &self.layout
+    }
+
+    pub fn line_rects(&self) -> &[LineRect]
+    {
+        &self.layout.lines
+    }
+
     pub fn set_dynamic_scale(&mut self, dynamic_scale: Option<Vector2<f32>>)
     {
         self.dynamic_scale = dynamic_scale;
@@ -346,22 +633,16 @@ impl TextObject
         }
     }
 
-    fn canvas_to_texture(
-        resource_uploader: &mut ResourceUploader,
-        canvas: Canvas,
-        location: UniformLocation,
-        shader: ShaderId
-    ) -> Arc<RwLock<Texture>>
+    fn canvas_to_image(canvas: Canvas, colors: &[[u8; 3]], gamma: f32) -> SimpleImage
     {
-        let colors = canvas.pixels.into_iter().map(|value|
+        let colors = canvas.pixels.into_iter().zip(colors.iter()).map(|(value, [r, g, b])|
         {
-            Color::new(u8::MAX, u8::MAX, u8::MAX, value)
-        }).collect::<Vec<_>>();
+            let amount = (value as f32 / u8::MAX as f32).powf(gamma);
 
-        let image = SimpleImage::new(colors, canvas.size.x() as usize, canvas.size.y() as usize);
-        let texture = Texture::new(resource_uploader, image.into(), location, shader);
+            Color::new(*r, *g, *b, (amount * u8::MAX as f32).round() as u8)
+        }).collect::<Vec<_>>();
 
-        Arc::new(RwLock::new(texture))
+        SimpleImage::new(colors, canvas.size.x() as usize, canvas.size.y() as usize)
     }
 
     fn with_font(
@@ -425,16 +706,30 @@ pub struct OriginOffset
     pub y: i32
 }
 
+type GlyphBitmap = Option<Rc<Canvas>>;
+
 pub struct CharsRasterizer
 {
-    font: Font
+    font: Font,
+    // rasterized glyphs by character and font size, none if the font doesnt have it
+    glyph_cache: RefCell<HashMap<(char, u32), GlyphBitmap>>
 }
 
 impl CharsRasterizer
 {
     pub fn new(font: Font) -> Self
     {
-        Self{font}
+        Self{font, glyph_cache: RefCell::new(HashMap::new())}
+    }
+
+    pub fn clear_cache(&self)
+    {
+        self.glyph_cache.borrow_mut().clear();
+    }
+
+    pub fn cached_glyphs(&self) -> usize
+    {
+        self.glyph_cache.borrow().len()
     }
 
     pub fn metrics(&self) -> Metrics
@@ -518,25 +813,25 @@ impl CharsRasterizer
         }
     }
 
+    // colors has a color for every pixel of the canvas
     pub fn render_glyph(
         &self,
         canvas: &mut Canvas,
+        colors: &mut [[u8; 3]],
         height: i32,
         font_size: u32,
-        char_x: i32,
-        char_y: usize,
-        c: char
+        info: CharInfo
     ) -> Option<()>
     {
-        let small = self.render_small(font_size, height, c)?;
+        let small = self.glyph(font_size, height, info.c)?;
 
-        let start_x = char_x.max(0) as usize;
+        let start_x = info.x.max(0) as usize;
 
         let big_width = canvas.size.x() as usize;
 
         let width = small.size.x() as usize;
 
-        let y_offset = char_y * height as usize;
+        let y_offset = info.line * height as usize;
 
         for y in 0..height as usize
         {
@@ -551,14 +846,38 @@ impl CharsRasterizer
                 let small_y = y * width;
                 let big_y = (y + y_offset) * big_width;
 
-                let this_pixel = &mut canvas.pixels[big_y + offset_x];
-                *this_pixel = this_pixel.saturating_add(small.pixels[small_y + x]);
+                let index = big_y + offset_x;
+                let value = small.pixels[small_y + x];
+
+                let this_pixel = &mut canvas.pixels[index];
+
+                // overlapping glyphs take the color of whichever covers more
+                if value > *this_pixel
+                {
+                    colors[index] = info.color;
+                }
+
+                *this_pixel = this_pixel.saturating_add(value);
             }
         }
 
         Some(())
     }
 
+    fn glyph(
+        &self,
+        font_size: u32,
+        canvas_height: i32,
+        c: char
+    ) -> GlyphBitmap
+    {
+        // the height only depends on the font size so it doesnt need to be in the key
+        self.glyph_cache.borrow_mut().entry((c, font_size)).or_insert_with(||
+        {
+            self.render_small(font_size, canvas_height, c).map(Rc::new)
+        }).clone()
+    }
+
     fn render_small(
         &self,
         font_size: u32,
@@ -0,0 +1,113 @@
+use std::{
+    fmt,
+    mem,
+    cell::{RefCell, RefMut},
+    sync::Arc
+};
+
+use vulkano::{
+    Validated,
+    VulkanError,
+    sync::{self, GpuFuture},
+    device::{Device, Queue},
+    command_buffer::{
+        AutoCommandBufferBuilder,
+        CommandBufferUsage,
+        allocator::StandardCommandBufferAllocator
+    }
+};
+
+use crate::{
+    game_object::CommandBuilderType,
+    window::DeviceLost
+};
+
+
+// copies recorded here run on their own queue alongside the rendering and the next graphics
+// submission waits on them, texture copies get uploaded here and their mips get blitted on
+// the graphics queue after (buffers are updated every frame so they stay on it)
+pub struct TransferQueue
+{
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    graphics_family: u32,
+    command_allocator: StandardCommandBufferAllocator,
+    builder: RefCell<Option<CommandBuilderType>>
+}
+
+impl TransferQueue
+{
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>, graphics_family: u32) -> Self
+    {
+        let command_allocator = StandardCommandBufferAllocator::new(device.clone(), Default::default());
+
+        Self{device, queue, graphics_family, command_allocator, builder: RefCell::new(None)}
+    }
+
+    pub fn queue(&self) -> &Arc<Queue>
+    {
+        &self.queue
+    }
+
+    // resources touched by both queues are shared concurrently so they dont need
+    // ownership transfers between the queue families
+    pub fn queue_families(&self) -> [u32; 2]
+    {
+        [self.graphics_family, self.queue.queue_family_index()]
+    }
+
+    pub fn builder(&self) -> RefMut<'_, CommandBuilderType>
+    {
+        RefMut::map(self.builder.borrow_mut(), |builder|
+        {
+            builder.get_or_insert_with(||
+            {
+                AutoCommandBufferBuilder::primary(
+                    &self.command_allocator,
+                    self.queue.queue_family_index(),
+                    CommandBufferUsage::OneTimeSubmit
+                ).unwrap()
+            })
+        })
+    }
+
+    pub(crate) fn flush(&self) -> Result<Option<Box<dyn GpuFuture>>, DeviceLost>
+    {
+        let builder = if let Some(builder) = self.builder.take()
+        {
+            builder
+        } else
+        {
+            return Ok(None);
+        };
+
+        let future = sync::now(self.device.clone())
+            .then_execute(self.queue.clone(), builder.build().unwrap())
+            .unwrap()
+            .then_signal_semaphore();
+
+        match future.flush()
+        {
+            Ok(()) => Ok(Some(Box::new(future))),
+            Err(Validated::Error(VulkanError::DeviceLost)) =>
+            {
+                // dropping it would wait on the lost device and panic
+                mem::forget(future);
+
+                Err(DeviceLost)
+            },
+            Err(e) => panic!("error flushing transfers: {e}")
+        }
+    }
+}
+
+impl fmt::Debug for TransferQueue
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_struct("TransferQueue")
+            .field("queue", &self.queue)
+            .field("graphics_family", &self.graphics_family)
+            .finish()
+    }
+}
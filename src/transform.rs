@@ -1,8 +1,11 @@
+use std::f32;
+
 use serde::{Serialize, Deserialize};
 
 use nalgebra::{
 	Vector2,
-	Vector3
+	Vector3,
+	Matrix4
 };
 
 
@@ -12,6 +15,7 @@ pub struct Transform
 	pub rotation: f32,
 	pub position: Vector3<f32>,
 	pub scale: Vector3<f32>,
+	// (angle, scale) scales by scale.x along the angle and by scale.y perpendicular to it
 	pub stretch: (f32, Vector2<f32>)
 }
 
@@ -57,6 +61,17 @@ impl Transform
 		value0 * (1.0 - amount) + value1 * amount
 	}
 
+	// goes the shorter way around the circle
+	pub fn interpolate_angle(value0: f32, value1: f32, amount: f32) -> f32
+	{
+		let tau = f32::consts::TAU;
+
+		let difference = (value1 - value0).rem_euclid(tau);
+		let difference = if difference > f32::consts::PI { difference - tau } else { difference };
+
+		value0 + difference * amount
+	}
+
 	pub fn interpolate_vector(
 		value0: Vector3<f32>,
 		value1: Vector3<f32>,
@@ -70,11 +85,79 @@ impl Transform
 		)
 	}
 
+	// the rotation goes the shorter way around like interpolate_angle
+	pub fn lerp(&self, other: &Self, amount: f32) -> Self
+	{
+		Self{
+			rotation: Self::interpolate_angle(self.rotation, other.rotation, amount),
+			position: Self::interpolate_vector(self.position, other.position, amount),
+			scale: Self::interpolate_vector(self.scale, other.scale, amount),
+			stretch: (
+				Self::interpolate(self.stretch.0, other.stretch.0, amount),
+				self.stretch.1.lerp(&other.stretch.1, amount)
+			)
+		}
+	}
+
+	// inverse of the object matrix without a stretch, origin or pivot
+	// none if the matrix has shear or rotation around other axes
+	pub fn from_matrix(matrix: Matrix4<f32>) -> Option<Self>
+	{
+		const EPSILON: f32 = 0.0001;
+
+		let close = |a: f32, b: f32| (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0);
+
+		let bottom = matrix.fixed_view::<1, 4>(3, 0);
+		if !(close(bottom[0], 0.0) && close(bottom[1], 0.0) && close(bottom[2], 0.0) && close(bottom[3], 1.0))
+		{
+			return None;
+		}
+
+		let x_axis: Vector3<f32> = matrix.fixed_view::<3, 1>(0, 0).into();
+		let y_axis: Vector3<f32> = matrix.fixed_view::<3, 1>(0, 1).into();
+		let z_axis: Vector3<f32> = matrix.fixed_view::<3, 1>(0, 2).into();
+
+		let flat = close(x_axis.z, 0.0) && close(y_axis.z, 0.0)
+			&& close(z_axis.x, 0.0) && close(z_axis.y, 0.0);
+
+		let scale_x = x_axis.magnitude();
+		let scale_y = y_axis.magnitude();
+
+		let perpendicular = close(x_axis.dot(&y_axis) / (scale_x * scale_y).max(f32::EPSILON), 0.0);
+
+		if !flat || !perpendicular
+		{
+			return None;
+		}
+
+		// a mirrored y axis shows up as a negative y scale
+		let scale_y = if x_axis.xy().perp(&y_axis.xy()) < 0.0 { -scale_y } else { scale_y };
+
+		Some(Self{
+			rotation: x_axis.y.atan2(x_axis.x),
+			position: matrix.fixed_view::<3, 1>(0, 3).into(),
+			scale: Vector3::new(scale_x, scale_y, z_axis.z),
+			..Default::default()
+		})
+	}
+
 	pub fn distance_associated(value0: Vector3<f32>, value1: Vector3<f32>) -> f32
 	{
 		(value1 - value0).magnitude()
 	}
 
+	// scales by amount along the angle and by 1 / amount perpendicular to it, keeping the area the same
+	pub fn stretch_along(angle: f32, amount: f32) -> (f32, Vector2<f32>)
+	{
+		(angle, Vector2::new(amount, amount.recip()))
+	}
+
+	// stretches vertically and squashes horizontally
+	pub fn squash_stretch(factor: f32) -> (f32, Vector2<f32>)
+	{
+		Self::stretch_along(f32::consts::FRAC_PI_2, factor)
+	}
+
     pub fn max_scale(&self) -> f32
     {
         let scale = self.scale;
@@ -218,6 +301,13 @@ pub trait TransformContainer: OnTransformCallback
 		self.rotation_callback(rotation);
 	}
 
+	fn rotate_to(&mut self, target: f32, amount: f32)
+	{
+		let new_rotation = Transform::interpolate_angle(self.rotation(), target, amount);
+
+		self.set_rotation(new_rotation);
+	}
+
 	fn half(&self) -> Vector3<f32>
 	{
 		self.transform_ref().half()
@@ -228,4 +318,53 @@ pub trait TransformContainer: OnTransformCallback
 		self.transform_mut().stretch = stretch;
 		self.stretch_callback(stretch);
 	}
+
+	fn set_stretch_along(&mut self, angle: f32, amount: f32)
+	{
+		self.set_stretch(Transform::stretch_along(angle, amount));
+	}
+
+	fn set_squash_stretch(&mut self, factor: f32)
+	{
+		self.set_stretch(Transform::squash_stretch(factor));
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	fn close(a: f32, b: f32) -> bool
+	{
+		(a - b).abs() < 0.0001
+	}
+
+	#[test]
+	fn interpolate_angle_short_way()
+	{
+		let half = Transform::interpolate_angle(3.0, -3.0, 0.5);
+		assert!(close(half, f32::consts::PI), "{half} isnt pi");
+
+		let quarter = Transform::interpolate_angle(3.0, -3.0, 0.25);
+		assert!(quarter > 3.0 && quarter < f32::consts::PI, "{quarter} went the long way");
+
+		let end = Transform::interpolate_angle(3.0, -3.0, 1.0);
+		assert!(close(end - f32::consts::TAU, -3.0), "{end} isnt the target");
+
+		let back = Transform::interpolate_angle(-3.0, 3.0, 0.5);
+		assert!(close(back, -f32::consts::PI), "{back} isnt -pi");
+	}
+
+	// lerp interpolates rotations as angles too
+	#[test]
+	fn lerp_rotation_short_way()
+	{
+		let a = Transform{rotation: 3.0, ..Default::default()};
+		let b = Transform{rotation: -3.0, ..Default::default()};
+
+		let rotation = a.lerp(&b, 0.5).rotation;
+		assert!(close(rotation, f32::consts::PI), "{rotation} isnt pi");
+	}
 }
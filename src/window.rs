@@ -1,4 +1,9 @@
 use std::{
+    iter,
+    mem,
+    error,
+    fmt::{self, Display},
+    rc::Rc,
     time::Instant,
     sync::Arc
 };
@@ -7,10 +12,12 @@ use vulkano::{
     Validated,
     VulkanError,
     format::{Format, NumericFormat, ClearValue},
-    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    buffer::{Buffer, Subbuffer, BufferUsage, BufferCreateInfo},
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
     descriptor_set::allocator::StandardDescriptorSetAllocator,
     shader::EntryPoint,
     sync::{
+        self,
         GpuFuture,
         future::FenceSignalFuture
     },
@@ -25,16 +32,18 @@ use vulkano::{
             multisample::MultisampleState,
             depth_stencil::{DepthStencilState, DepthState, StencilState},
             color_blend::{ColorBlendState, ColorBlendAttachmentState, AttachmentBlend},
-            rasterization::{CullMode, RasterizationState},
-            input_assembly::InputAssemblyState,
+            rasterization::{CullMode, DepthBiasState, RasterizationState},
+            input_assembly::{InputAssemblyState, PrimitiveTopology},
             vertex_input::{VertexBufferDescription, VertexDefinition},
             viewport::{Scissor, Viewport, ViewportState}
         }
     },
     image::{
         ImageUsage,
+        ImageLayout,
         Image,
         ImageType,
+        SampleCount,
         ImageCreateInfo,
         view::ImageView,
         sampler::{
@@ -58,17 +67,25 @@ use vulkano::{
     },
     device::{
         Device,
+        DeviceOwned,
         physical::PhysicalDevice,
         Queue
     },
     render_pass::{
         Subpass,
+        SubpassDescription,
         RenderPass,
+        RenderPassCreateInfo,
+        AttachmentDescription,
+        AttachmentReference,
+        AttachmentLoadOp,
+        AttachmentStoreOp,
         Framebuffer,
         FramebufferCreateInfo
     },
     command_buffer::{
         AutoCommandBufferBuilder,
+        CopyImageToBufferInfo,
         PrimaryAutoCommandBuffer,
         CommandBufferExecFuture,
         CommandBufferUsage,
@@ -85,21 +102,35 @@ use winit::{
     event::{
         Event,
         WindowEvent,
-        MouseScrollDelta
+        DeviceEvent,
+        MouseScrollDelta,
+        ElementState,
+        Touch,
+        Ime
     },
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget}
 };
 
+use nalgebra::Vector2;
+
 use crate::{
     YanyaApp,
     AppOptions,
+    AppError,
+    SurfaceFormatChooser,
     Control,
+    ComputeQueue,
+    TransferQueue,
     ShadersGroup,
     engine::Engine,
+    render_target::OffscreenCreator,
     game_object::*,
-    object::resource_uploader::ResourceUploader
+    object::{resource_uploader::ResourceUploader, texture::RgbaImage}
 };
 
+#[cfg(feature = "gamepad")]
+use crate::gamepad::Gamepads;
+
 
 pub struct PipelineInfo
 {
@@ -122,161 +153,685 @@ pub struct PipelineCreateInfo
 {
     pub stages: Vec<PipelineShaderStageCreateInfo>,
     pub per_vertex: VertexBufferDescription,
+    pub per_instance: Option<VertexBufferDescription>,
     pub shaders: ShadersGroup<EntryPoint>,
     pub layout: Arc<PipelineLayout>,
     pub depth: Option<DepthState>,
-    pub stencil: Option<StencilState>
+    pub stencil: Option<StencilState>,
+    pub blend: Option<AttachmentBlend>,
+    pub topology: PrimitiveTopology,
+    pub subpass: u32
 }
 
 pub type AttachmentCreator<T> = Box<dyn Fn(T, Arc<StandardMemoryAllocator>, Arc<ImageView>) -> Vec<Arc<ImageView>>>;
-pub type RenderPassCreator<T> = Box<dyn FnOnce(T, Arc<Device>, Format) -> Arc<RenderPass>>;
+pub type RenderPassCreator<T> = Box<dyn Fn(T, Arc<Device>, Format) -> Arc<RenderPass>>;
+
+// makes a new device with everything on it, called again after the old one gets lost
+pub type GraphicsCreator<T> = Box<dyn Fn(Option<Arc<Surface>>) -> Result<GraphicsInfo<T>, AppError>>;
+
+type SwapchainImages = (Arc<Swapchain>, Vec<Arc<Image>>);
+
+// execute_builder waits for the previous frame before submitting the next one, no matter
+// how many swapchain images there are
+const FRAMES_IN_FLIGHT: usize = 1;
 
 pub struct Rendering<T>
 {
-    pub setup: Box<dyn FnOnce(Arc<PhysicalDevice>) -> T>,
+    pub setup: Box<dyn Fn(Arc<PhysicalDevice>) -> T>,
     pub attachments: AttachmentCreator<T>,
     pub render_pass: RenderPassCreator<T>,
     pub clear: Vec<Option<ClearValue>>
 }
 
-impl Rendering<()>
+// the builtin renderings are all built from this, so changing one part of it
+// (like the depth clear) keeps everything else the same
+#[derive(Debug, Clone)]
+pub struct RenderingConfig
 {
-    pub fn new_default(
-        clear_color: ClearValue
-    ) -> Self
+    pub clear_color: ClearValue,
+    // without depth theres no depth attachment at all, use Shader::flat with it
+    pub depth: bool,
+    // no depth clear value means the depth attachment isnt cleared at all
+    pub depth_clear: Option<f32>,
+    // extra color attachments written by fragment shader outputs at locations 1 and up
+    pub targets: Vec<(Format, ClearValue)>,
+    // gets clamped down to what the device actually supports, with more than 1 sample
+    // every color attachment is drawn multisampled and resolved at the end
+    pub samples: SampleCount
+}
+
+impl RenderingConfig
+{
+    pub fn new(clear_color: ClearValue) -> Self
     {
-        let attachments = Box::new(|_, allocator: Arc<StandardMemoryAllocator>, view: Arc<ImageView>|
+        Self{
+            clear_color,
+            depth: true,
+            depth_clear: Some(1.0),
+            targets: Vec::new(),
+            samples: SampleCount::Sample1
+        }
+    }
+
+    fn supported_samples(&self, physical_device: &PhysicalDevice) -> SampleCount
+    {
+        let properties = physical_device.properties();
+
+        let mut supported = properties.framebuffer_color_sample_counts;
+        if self.depth
+        {
+            supported &= properties.framebuffer_depth_sample_counts;
+        }
+
+        [
+            SampleCount::Sample64,
+            SampleCount::Sample32,
+            SampleCount::Sample16,
+            SampleCount::Sample8,
+            SampleCount::Sample4,
+            SampleCount::Sample2
+        ].into_iter().find(|count|
+        {
+            (*count as u32) <= (self.samples as u32) && supported.contains_enum(*count)
+        }).unwrap_or(SampleCount::Sample1)
+    }
+
+    fn color_formats(&self, image_format: Format) -> Vec<Format>
+    {
+        iter::once(image_format).chain(self.targets.iter().map(|(format, _)| *format)).collect()
+    }
+
+    // the color attachments in shader location order, then depth, then with msaa
+    // the single sampled attachments the colors get resolved into
+    fn attachments(
+        &self,
+        allocator: Arc<StandardMemoryAllocator>,
+        view: Arc<ImageView>
+    ) -> Vec<Arc<ImageView>>
+    {
+        let samples = self.supported_samples(view.device().physical_device());
+        let multisampled = samples != SampleCount::Sample1;
+
+        let extent = view.image().extent();
+
+        let create_view = |format, samples, usage|
         {
-            let depth_image = Image::new(
-                allocator,
+            let image = Image::new(
+                allocator.clone(),
                 ImageCreateInfo{
                     image_type: ImageType::Dim2d,
-                    format: Format::D16_UNORM,
-                    extent: view.image().extent(),
-                    usage: ImageUsage::TRANSIENT_ATTACHMENT | ImageUsage::DEPTH_STENCIL_ATTACHMENT,
+                    format,
+                    extent,
+                    samples,
+                    usage,
                     ..Default::default()
                 },
                 AllocationCreateInfo::default()
             ).unwrap();
 
-            let depth = ImageView::new_default(depth_image).unwrap();
+            ImageView::new_default(image).unwrap()
+        };
+
+        let target_usage = ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED;
+
+        let mut views = Vec::new();
+
+        if multisampled
+        {
+            views.extend(self.color_formats(view.format()).into_iter().map(|format|
+            {
+                create_view(
+                    format,
+                    samples,
+                    ImageUsage::TRANSIENT_ATTACHMENT | ImageUsage::COLOR_ATTACHMENT
+                )
+            }));
+        } else
+        {
+            views.push(view.clone());
+            views.extend(self.targets.iter().map(|(format, _)|
+            {
+                create_view(*format, samples, target_usage)
+            }));
+        }
+
+        if self.depth
+        {
+            views.push(create_view(
+                Format::D16_UNORM,
+                samples,
+                ImageUsage::TRANSIENT_ATTACHMENT | ImageUsage::DEPTH_STENCIL_ATTACHMENT
+            ));
+        }
+
+        if multisampled
+        {
+            views.push(view);
+            views.extend(self.targets.iter().map(|(format, _)|
+            {
+                create_view(*format, SampleCount::Sample1, target_usage)
+            }));
+        }
+
+        views
+    }
+
+    fn render_pass(&self, device: Arc<Device>, image_format: Format) -> Arc<RenderPass>
+    {
+        let samples = self.supported_samples(device.physical_device());
+        let multisampled = samples != SampleCount::Sample1;
+
+        let color_formats = self.color_formats(image_format);
+
+        let color_attachment = |format, samples, load_op, store_op|
+        {
+            AttachmentDescription{
+                format,
+                samples,
+                load_op,
+                store_op,
+                initial_layout: ImageLayout::ColorAttachmentOptimal,
+                final_layout: ImageLayout::ColorAttachmentOptimal,
+                ..Default::default()
+            }
+        };
+
+        let color_reference = |attachment|
+        {
+            Some(AttachmentReference{
+                attachment,
+                layout: ImageLayout::ColorAttachmentOptimal,
+                ..Default::default()
+            })
+        };
+
+        // multisampled colors only live until theyre resolved
+        let color_store = if multisampled { AttachmentStoreOp::DontCare } else { AttachmentStoreOp::Store };
+
+        // the swapchain image is always the first color attachment (location 0 in the shader)
+        let mut attachments: Vec<_> = color_formats.iter().map(|format|
+        {
+            color_attachment(*format, samples, AttachmentLoadOp::Clear, color_store)
+        }).collect();
+
+        let color_attachments = (0..attachments.len() as u32).map(color_reference).collect();
+
+        let depth_stencil_attachment = self.depth.then(||
+        {
+            let attachment = attachments.len() as u32;
+            attachments.push(AttachmentDescription{
+                format: Format::D16_UNORM,
+                samples,
+                load_op: if self.depth_clear.is_some()
+                {
+                    AttachmentLoadOp::Clear
+                } else
+                {
+                    AttachmentLoadOp::DontCare
+                },
+                store_op: AttachmentStoreOp::DontCare,
+                initial_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                ..Default::default()
+            });
+
+            AttachmentReference{
+                attachment,
+                layout: ImageLayout::DepthStencilAttachmentOptimal,
+                ..Default::default()
+            }
+        });
+
+        let color_resolve_attachments = if multisampled
+        {
+            let start = attachments.len() as u32;
+            attachments.extend(color_formats.iter().map(|format|
+            {
+                color_attachment(
+                    *format,
+                    SampleCount::Sample1,
+                    AttachmentLoadOp::DontCare,
+                    AttachmentStoreOp::Store
+                )
+            }));
+
+            (start..attachments.len() as u32).map(color_reference).collect()
+        } else
+        {
+            Vec::new()
+        };
+
+        RenderPass::new(
+            device,
+            RenderPassCreateInfo{
+                attachments,
+                subpasses: vec![SubpassDescription{
+                    color_attachments,
+                    color_resolve_attachments,
+                    depth_stencil_attachment,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }
+        ).unwrap()
+    }
+
+    // the resolve attachments at the end dont get cleared so they arent here
+    fn clear_values(&self) -> Vec<Option<ClearValue>>
+    {
+        let mut clear = vec![Some(self.clear_color)];
+        clear.extend(self.targets.iter().map(|(_, value)| Some(*value)));
+
+        if self.depth
+        {
+            clear.push(self.depth_clear.map(ClearValue::from));
+        }
+
+        clear
+    }
+}
+
+impl From<RenderingConfig> for Rendering<()>
+{
+    fn from(config: RenderingConfig) -> Self
+    {
+        let clear = config.clear_values();
+
+        let config = Rc::new(config);
+
+        let setup = {
+            let config = config.clone();
+
+            Box::new(move |physical_device: Arc<PhysicalDevice>|
+            {
+                let chosen = config.supported_samples(&physical_device);
+
+                if chosen != config.samples
+                {
+                    eprintln!("{:?} isnt supported, using {chosen:?}", config.samples);
+                }
+            })
+        };
+
+        let attachments = {
+            let config = config.clone();
+
+            Box::new(move |_, allocator, view| config.attachments(allocator, view))
+        };
+
+        let render_pass = Box::new(move |_, device, image_format|
+        {
+            config.render_pass(device, image_format)
+        });
+
+        Self{
+            setup,
+            attachments,
+            render_pass,
+            clear
+        }
+    }
+}
+
+impl Rendering<()>
+{
+    pub fn new_default(
+        clear_color: ClearValue
+    ) -> Self
+    {
+        RenderingConfig::new(clear_color).into()
+    }
+
+    // no depth clear value means the depth attachment isnt cleared at all
+    pub fn new_default_with_depth(
+        clear_color: ClearValue,
+        depth_clear: Option<f32>
+    ) -> Self
+    {
+        RenderingConfig{
+            depth_clear,
+            ..RenderingConfig::new(clear_color)
+        }.into()
+    }
+
+    // every target gets its own location after the swapchain image
+    pub fn new_multiple_targets(
+        clear_color: ClearValue,
+        targets: Vec<(Format, ClearValue)>
+    ) -> Self
+    {
+        RenderingConfig{
+            targets,
+            ..RenderingConfig::new(clear_color)
+        }.into()
+    }
+
+    // subpass 0 draws the scene with depth into an intermediate image, subpass 1 only has
+    // the swapchain image as output and the scene as input attachment 0 (see Shader::post_process)
+    pub fn new_post_process(
+        clear_color: ClearValue
+    ) -> Self
+    {
+        let attachments = Box::new(|_, allocator: Arc<StandardMemoryAllocator>, view: Arc<ImageView>|
+        {
+            let extent = view.image().extent();
+
+            let create_view = |format, usage|
+            {
+                let image = Image::new(
+                    allocator.clone(),
+                    ImageCreateInfo{
+                        image_type: ImageType::Dim2d,
+                        format,
+                        extent,
+                        usage,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo::default()
+                ).unwrap();
+
+                ImageView::new_default(image).unwrap()
+            };
+
+            let scene = create_view(
+                view.format(),
+                ImageUsage::TRANSIENT_ATTACHMENT
+                    | ImageUsage::COLOR_ATTACHMENT
+                    | ImageUsage::INPUT_ATTACHMENT
+            );
 
-            vec![view, depth]
+            let depth = create_view(
+                Format::D16_UNORM,
+                ImageUsage::TRANSIENT_ATTACHMENT | ImageUsage::DEPTH_STENCIL_ATTACHMENT
+            );
+
+            vec![scene, depth, view]
         });
 
         let render_pass = Box::new(|_, device, image_format|
         {
-            vulkano::single_pass_renderpass!(
+            vulkano::ordered_passes_renderpass!(
                 device,
                 attachments: {
-                    color: {
+                    scene: {
                         format: image_format,
                         samples: 1,
                         load_op: Clear,
-                        store_op: Store
+                        store_op: DontCare
                     },
                     depth: {
                         format: Format::D16_UNORM,
                         samples: 1,
                         load_op: Clear,
                         store_op: DontCare
+                    },
+                    color: {
+                        format: image_format,
+                        samples: 1,
+                        load_op: DontCare,
+                        store_op: Store
                     }
                 },
-                pass: {
-                    color: [color],
-                    depth_stencil: {depth}
-                }
+                passes: [
+                    {
+                        color: [scene],
+                        depth_stencil: {depth},
+                        input: []
+                    },
+                    {
+                        color: [color],
+                        depth_stencil: {},
+                        input: [scene]
+                    }
+                ]
             ).unwrap()
         });
 
-        let clear = vec![Some(clear_color), Some(1.0.into())];
-
         Self{
             setup: Box::new(|_| {}),
             attachments,
             render_pass,
-            clear
+            clear: vec![Some(clear_color), Some(ClearValue::from(1.0)), None]
         }
     }
+
+    // the sample count gets clamped down to what the device actually supports
+    pub fn new_msaa(
+        clear_color: ClearValue,
+        samples: SampleCount
+    ) -> Self
+    {
+        RenderingConfig{
+            samples,
+            ..RenderingConfig::new(clear_color)
+        }.into()
+    }
+
+    // color only, for stuff that never needs depth testing (like ui overlays)
+    pub fn new_flat(
+        clear_color: ClearValue
+    ) -> Self
+    {
+        RenderingConfig{
+            depth: false,
+            ..RenderingConfig::new(clear_color)
+        }.into()
+    }
+}
+
+enum Presentation
+{
+    Swapchain{surface: Arc<Surface>, swapchain: Arc<Swapchain>},
+    Headless{extent: [u32; 2]}
+}
+
+impl Presentation
+{
+    fn size(&self) -> PhysicalSize<u32>
+    {
+        match self
+        {
+            Self::Swapchain{surface, ..} => Self::surface_size(surface),
+            Self::Headless{extent} => (*extent).into()
+        }
+    }
+
+    fn surface_size(surface: &Arc<Surface>) -> PhysicalSize<u32>
+    {
+        let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
+
+        window.inner_size()
+    }
 }
 
 // just put everything in 1 place who cares lmao
 struct RenderInfo<T>
 {
     pub device: Arc<Device>,
-    pub swapchain: Arc<Swapchain>,
+    presentation: Presentation,
+    pub image_format: Format,
     pub framebuffers: Box<[Arc<Framebuffer>]>,
+    pub images: Box<[Arc<Image>]>,
     pub pipelines: Vec<PipelineInfo>,
     pub viewport: Viewport,
-    pub surface: Arc<Surface>,
     pub render_pass: Arc<RenderPass>,
     pub sampler: Arc<Sampler>,
     pub clear_values: Vec<Option<ClearValue>>,
     pipeline_infos: Vec<PipelineCreateInfo>,
     pub memory_allocator: Arc<StandardMemoryAllocator>,
     descriptor_allocator: Arc<StandardDescriptorSetAllocator>,
+    transfer: Option<TransferQueue>,
     setup: T,
-    attachment_creator: AttachmentCreator<T>
+    rendering: Rc<Rendering<T>>
 }
 
 impl<T: Clone> RenderInfo<T>
 {
     pub fn new(
         info: GraphicsInfo<T>,
-        capabilities: SurfaceCapabilities,
-        image_format: Format,
-        composite_alpha: CompositeAlpha
+        surface_format: Option<&SurfaceFormatChooser>
     ) -> Self
     {
-        let device = info.device;
-        let surface = info.surface;
-        let pipeline_infos = info.pipeline_infos;
+        let device = info.device.clone();
+        let surface = info.surface.clone().expect("windowed rendering needs a surface");
 
-        let sampler = Sampler::new(
-            device.clone(),
-            SamplerCreateInfo{
-                mag_filter: Filter::Nearest,
-                min_filter: Filter::Linear,
-                mipmap_mode: SamplerMipmapMode::Linear,
-                ..Default::default()
-            }
-        ).unwrap();
+        let capabilities = info.physical_device
+            .surface_capabilities(&surface, Default::default())
+            .unwrap();
+
+        let composite_alpha = Self::composite_alpha(&capabilities);
+
+        let formats = info.physical_device
+            .surface_formats(&surface, Default::default())
+            .unwrap();
+
+        let (image_format, image_color_space) = surface_format.and_then(|f| f(&formats))
+            .unwrap_or_else(||
+            {
+                *formats.iter().find(|(format, colorspace)|
+                {
+                    format.numeric_format_color() == Some(NumericFormat::SRGB)
+                        && *colorspace == ColorSpace::SrgbNonLinear
+                }).unwrap_or_else(|| &formats[0])
+            });
 
         let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
 
-        let dimensions = Self::surface_size_associated(&surface);
+        let dimensions = Presentation::surface_size(&surface);
 
         eprintln!("framebuffer format: {image_format:?}");
 
+        let min_image_count = info.image_count.map(|count|
+        {
+            let count = count.max(capabilities.min_image_count);
+
+            capabilities.max_image_count.map(|max| count.min(max)).unwrap_or(count)
+        }).unwrap_or_else(|| capabilities.min_image_count.max(2));
+
+        // capturing copies out of the swapchain images, not every surface allows that
+        let capture_usage = if capabilities.supported_usage_flags.intersects(ImageUsage::TRANSFER_SRC)
+        {
+            ImageUsage::TRANSFER_SRC
+        } else
+        {
+            ImageUsage::empty()
+        };
+
         let (swapchain, images) = Swapchain::new(
-            device.clone(),
+            device,
             surface.clone(),
             SwapchainCreateInfo{
-                min_image_count: capabilities.min_image_count.max(2),
+                min_image_count,
                 image_format,
+                image_color_space,
                 image_extent: dimensions.into(),
-                image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+                image_usage: ImageUsage::COLOR_ATTACHMENT
+                    | ImageUsage::TRANSFER_DST
+                    | capture_usage,
                 composite_alpha,
                 ..Default::default()
             }
         ).unwrap();
 
-        let setup = (info.rendering.setup)(info.physical_device.clone());
-        let render_pass = (info.rendering.render_pass)(setup.clone(), device.clone(), image_format);
+        Self::from_images(
+            info,
+            memory_allocator,
+            Presentation::Swapchain{surface, swapchain},
+            images.into(),
+            image_format
+        )
+    }
+
+    fn composite_alpha(capabilities: &SurfaceCapabilities) -> CompositeAlpha
+    {
+        let supported = capabilities.supported_composite_alpha;
+
+        let preferred = CompositeAlpha::Opaque;
+        let supports_preferred = supported.contains_enum(preferred);
+
+        if supports_preferred
+        {
+            preferred
+        } else
+        {
+            supported.into_iter().next().unwrap()
+        }
+    }
+
+    // renders into a single image that never gets presented
+    pub fn new_headless(
+        info: GraphicsInfo<T>,
+        extent: [u32; 2]
+    ) -> Self
+    {
+        let image_format = Format::R8G8B8A8_SRGB;
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(info.device.clone()));
+
+        let image = Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo{
+                image_type: ImageType::Dim2d,
+                format: image_format,
+                extent: [extent[0], extent[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default()
+        ).unwrap();
+
+        Self::from_images(
+            info,
+            memory_allocator,
+            Presentation::Headless{extent},
+            Box::new([image]),
+            image_format
+        )
+    }
+
+    fn from_images(
+        info: GraphicsInfo<T>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        presentation: Presentation,
+        images: Box<[Arc<Image>]>,
+        image_format: Format
+    ) -> Self
+    {
+        let device = info.device;
+        let pipeline_infos = info.pipeline_infos;
+
+        let graphics_family = info.queues.graphics.queue_family_index();
+        let transfer = info.queues.transfer.map(|queue|
+        {
+            TransferQueue::new(device.clone(), queue, graphics_family)
+        });
+
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo{
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Linear,
+                mipmap_mode: SamplerMipmapMode::Linear,
+                ..Default::default()
+            }
+        ).unwrap();
+
+        let rendering = info.rendering;
 
-        let attachment_creator = info.rendering.attachments;
+        let setup = (rendering.setup)(info.physical_device.clone());
+        let render_pass = (rendering.render_pass)(setup.clone(), device.clone(), image_format);
 
         let framebuffers = Self::framebuffers(
             memory_allocator.clone(),
-            images.into_iter(),
+            images.iter().cloned(),
             render_pass.clone(),
             &setup,
-            &attachment_creator
+            &rendering.attachments
         );
 
         let viewport = Viewport{
             offset: [0.0, 0.0],
-            extent: dimensions.into(),
+            extent: presentation.size().into(),
             depth_range: 0.0..=1.0
         };
 
@@ -293,21 +848,27 @@ impl<T: Clone> RenderInfo<T>
             Default::default()
         ));
 
+        // attachments past the end of the clear values (like resolve ones) arent cleared
+        let mut clear_values = rendering.clear.clone();
+        clear_values.resize(render_pass.attachments().len(), None);
+
         Self{
             device,
-            swapchain,
+            presentation,
+            image_format,
             framebuffers,
+            images,
             pipelines,
             viewport,
-            surface,
             render_pass,
             sampler,
-            clear_values: info.rendering.clear,
+            clear_values,
             pipeline_infos,
             memory_allocator,
             descriptor_allocator,
+            transfer,
             setup,
-            attachment_creator
+            rendering
         }
     }
 
@@ -344,23 +905,36 @@ impl<T: Clone> RenderInfo<T>
     {
         let mut dynamic_state = ahash::HashSet::default();
         dynamic_state.insert(DynamicState::Scissor);
+        dynamic_state.insert(DynamicState::DepthBias);
+        dynamic_state.insert(DynamicState::Viewport);
 
         let pipeline = GraphicsPipeline::new(
             device,
             None,
             GraphicsPipelineCreateInfo{
                 stages: shader.stages.iter().cloned().collect(),
-                vertex_input_state: Some(shader.per_vertex
-                    .definition(&shader.shaders.vertex.info().input_interface)
-                    .unwrap()
+                vertex_input_state: Some(
+                    iter::once(shader.per_vertex.clone())
+                        .chain(shader.per_instance.clone())
+                        .collect::<Vec<_>>()
+                        .definition(&shader.shaders.vertex.info().input_interface)
+                        .unwrap()
                 ),
-                input_assembly_state: Some(InputAssemblyState::default()),
+                input_assembly_state: Some(InputAssemblyState{
+                    topology: shader.topology,
+                    ..Default::default()
+                }),
                 viewport_state: Some(ViewportState{
                     viewports: [viewport].into_iter().collect(),
                     ..Default::default()
                 }),
                 rasterization_state: Some(RasterizationState{
                     cull_mode: CullMode::None,
+                    depth_bias: Some(DepthBiasState{
+                        constant_factor: 0.0,
+                        clamp: 0.0,
+                        slope_factor: 0.0
+                    }),
                     ..Default::default()
                 }),
                 multisample_state: Some(MultisampleState{
@@ -370,14 +944,17 @@ impl<T: Clone> RenderInfo<T>
                 color_blend_state: Some(ColorBlendState::with_attachment_states(
                     subpass.num_color_attachments(),
                     ColorBlendAttachmentState{
-                        blend: Some(AttachmentBlend::alpha()),
+                        blend: shader.blend,
                         ..Default::default()
                     }
                 )),
-                depth_stencil_state: Some(DepthStencilState{
-                    depth: shader.depth.clone(),
-                    stencil: shader.stencil.clone(),
-                    ..Default::default()
+                depth_stencil_state: subpass.subpass_desc().depth_stencil_attachment.is_some().then(||
+                {
+                    DepthStencilState{
+                        depth: shader.depth.clone(),
+                        stencil: shader.stencil.clone(),
+                        ..Default::default()
+                    }
                 }),
                 dynamic_state,
                 subpass: Some(subpass.into()),
@@ -395,14 +972,15 @@ impl<T: Clone> RenderInfo<T>
         pipeline_infos: &[PipelineCreateInfo]
     ) -> Vec<PipelineInfo>
     {
-        let subpass = Subpass::from(render_pass, 0).unwrap();
-
         pipeline_infos.iter().map(|shader|
         {
+            let subpass = Subpass::from(render_pass.clone(), shader.subpass)
+                .unwrap_or_else(|| panic!("render pass doesnt have subpass #{}", shader.subpass));
+
             Self::generate_pipeline(
                 shader,
                 viewport.clone(),
-                subpass.clone(),
+                subpass,
                 device.clone()
             )
         }).collect()
@@ -418,10 +996,79 @@ impl<T: Clone> RenderInfo<T>
             descriptor_allocator: self.descriptor_allocator.clone(),
             sampler: self.sampler.clone(),
             builder,
+            transfer: self.transfer.as_ref(),
             pipeline_infos: &self.pipelines
         }
     }
 
+    // captures get read back as 8 bit rgba, so other formats cant be captured
+    pub fn capture_error(&self) -> Option<CaptureError>
+    {
+        let image = &self.images[0];
+
+        if !image.usage().intersects(ImageUsage::TRANSFER_SRC)
+        {
+            return Some(CaptureError::UnsupportedUsage);
+        }
+
+        let is_rgba8 = matches!(
+            image.format(),
+            Format::R8G8B8A8_SRGB | Format::R8G8B8A8_UNORM
+                | Format::B8G8R8A8_SRGB | Format::B8G8R8A8_UNORM
+        );
+
+        (!is_rgba8).then_some(CaptureError::UnsupportedFormat(image.format()))
+    }
+
+    pub fn flush_transfers(&self) -> Result<Option<Box<dyn GpuFuture>>, DeviceLost>
+    {
+        match self.transfer.as_ref()
+        {
+            Some(transfer) => transfer.flush(),
+            None => Ok(None)
+        }
+    }
+
+    pub fn offscreen_creator(&self) -> OffscreenCreator
+    where
+        T: 'static
+    {
+        let allocator = self.memory_allocator.clone();
+        let render_pass = self.render_pass.clone();
+        let format = self.image_format;
+        let setup = self.setup.clone();
+        let rendering = self.rendering.clone();
+
+        let create = Rc::new(move |[width, height]: [u32; 2]|
+        {
+            let image = Image::new(
+                allocator.clone(),
+                ImageCreateInfo{
+                    image_type: ImageType::Dim2d,
+                    format,
+                    extent: [width, height, 1],
+                    usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+                    ..Default::default()
+                },
+                AllocationCreateInfo::default()
+            ).unwrap();
+
+            let view = ImageView::new_default(image).unwrap();
+
+            let framebuffer = Framebuffer::new(
+                render_pass.clone(),
+                FramebufferCreateInfo{
+                    attachments: (rendering.attachments)(setup.clone(), allocator.clone(), view.clone()),
+                    ..Default::default()
+                }
+            ).unwrap();
+
+            (view, framebuffer)
+        });
+
+        OffscreenCreator{create, clear_values: self.clear_values.clone()}
+    }
+
     pub fn recreate(
         &mut self,
         redraw_window: bool
@@ -429,18 +1076,35 @@ impl<T: Clone> RenderInfo<T>
     {
         let dimensions = self.surface_size();
 
-        let (new_swapchain, new_images) = self.swapchain.recreate(SwapchainCreateInfo{
+        let swapchain = self.swapchain();
+        let create_info = SwapchainCreateInfo{
             image_extent: dimensions.into(),
-            ..self.swapchain.create_info()
-        })?;
+            ..swapchain.create_info()
+        };
+
+        let (new_swapchain, new_images) = match swapchain.recreate(create_info.clone())
+        {
+            Err(Validated::Error(VulkanError::SurfaceLost)) =>
+            {
+                eprintln!("surface lost, recreating it");
+
+                self.recreate_surface(create_info)?
+            },
+            x => x?
+        };
+
+        if let Presentation::Swapchain{swapchain, ..} = &mut self.presentation
+        {
+            *swapchain = new_swapchain;
+        }
 
-        self.swapchain = new_swapchain;
+        self.images = new_images.into();
         self.framebuffers = Self::framebuffers(
             self.memory_allocator.clone(),
-            new_images.into_iter(),
+            self.images.iter().cloned(),
             self.render_pass.clone(),
             &self.setup,
-            &self.attachment_creator
+            &self.rendering.attachments
         );
 
         if redraw_window
@@ -458,11 +1122,37 @@ impl<T: Clone> RenderInfo<T>
         Ok(())
     }
 
+    // the old swapchain cant be reused when its surface is gone
+    fn recreate_surface(
+        &mut self,
+        create_info: SwapchainCreateInfo
+    ) -> Result<SwapchainImages, Validated<VulkanError>>
+    {
+        let window = self.window().unwrap();
+        let surface = Surface::from_window(self.device.instance().clone(), window)?;
+
+        if let Presentation::Swapchain{surface: old_surface, ..} = &mut self.presentation
+        {
+            *old_surface = surface.clone();
+        }
+
+        Swapchain::new(self.device.clone(), surface, create_info)
+    }
+
     pub fn size(&self) -> [f32; 2]
     {
         self.surface_size().into()
     }
 
+    pub fn frame_info(&self, index: u64, elapsed: f64) -> FrameInfo
+    {
+        FrameInfo{
+            index,
+            in_flight: FRAMES_IN_FLIGHT,
+            elapsed
+        }
+    }
+
     pub fn aspect(&self) -> f32
     {
         let [x, y] = self.size();
@@ -472,25 +1162,48 @@ impl<T: Clone> RenderInfo<T>
 
     pub fn surface_size(&self) -> PhysicalSize<u32>
     {
-        Self::surface_size_associated(&self.surface)
+        self.presentation.size()
     }
 
-    fn surface_size_associated(surface: &Arc<Surface>) -> PhysicalSize<u32>
+    pub fn swapchain(&self) -> &Arc<Swapchain>
     {
-        let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
+        match &self.presentation
+        {
+            Presentation::Swapchain{swapchain, ..} => swapchain,
+            Presentation::Headless{..} => unreachable!("headless rendering has no swapchain")
+        }
+    }
 
-        window.inner_size()
+    pub fn window(&self) -> Option<Arc<Window>>
+    {
+        match &self.presentation
+        {
+            Presentation::Swapchain{surface, ..} =>
+            {
+                Some(surface.object().unwrap().clone().downcast::<Window>().unwrap())
+            },
+            Presentation::Headless{..} => None
+        }
     }
 }
 
+pub struct Queues
+{
+    pub graphics: Arc<Queue>,
+    pub compute: Option<Arc<Queue>>,
+    pub transfer: Option<Arc<Queue>>
+}
+
 pub struct GraphicsInfo<T>
 {
-    pub surface: Arc<Surface>,
+    // none when rendering headless
+    pub surface: Option<Arc<Surface>>,
     pub physical_device: Arc<PhysicalDevice>,
     pub device: Arc<Device>,
     pub pipeline_infos: Vec<PipelineCreateInfo>,
-    pub queues: Vec<Arc<Queue>>,
-    pub rendering: Rendering<T>
+    pub queues: Queues,
+    pub image_count: Option<u32>,
+    pub rendering: Rc<Rendering<T>>
 }
 
 // stupid code duplication but im lazy wutever
@@ -498,9 +1211,11 @@ struct HandleEventInfoRaw<T>
 {
     command_allocator: StandardCommandBufferAllocator,
     queue: Arc<Queue>,
+    compute_queue: Option<Arc<Queue>>,
     fence: FutureType,
     device: Arc<Device>,
     render_info: RenderInfo<T>,
+    graphics_creator: GraphicsCreator<T>,
     options: AppOptions
 }
 
@@ -508,16 +1223,25 @@ struct HandleEventInfo<UserApp, T>
 {
     command_allocator: StandardCommandBufferAllocator,
     queue: Arc<Queue>,
+    compute_queue: Option<Arc<Queue>>,
     fence: FutureType,
     device: Arc<Device>,
     render_info: RenderInfo<T>,
+    graphics_creator: GraphicsCreator<T>,
     options: AppOptions,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
     engine: Option<Engine>,
     user_app: Option<UserApp>,
     previous_time: Instant,
+    start_time: Instant,
+    fixed_accumulator: f32,
+    frame_index: u64,
     frame_parity: bool,
     initialized: bool,
     recreate_swapchain: bool,
+    recreate_failures: u32,
+    device_lost: bool,
     window_resized: bool
 }
 
@@ -528,67 +1252,43 @@ impl<UserApp, T> From<HandleEventInfoRaw<T>> for HandleEventInfo<UserApp, T>
         Self{
             command_allocator: value.command_allocator,
             queue: value.queue,
+            compute_queue: value.compute_queue,
             fence: value.fence,
             device: value.device,
             render_info: value.render_info,
+            graphics_creator: value.graphics_creator,
             options: value.options,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(),
             engine: None,
             user_app: None,
             previous_time: Instant::now(),
+            start_time: Instant::now(),
+            fixed_accumulator: 0.0,
+            frame_index: 0,
             frame_parity: false,
             initialized: false,
             recreate_swapchain: false,
+            recreate_failures: 0,
+            device_lost: false,
             window_resized: false
         }
     }
 }
 
-pub fn run<UserApp: YanyaApp + 'static, T: Clone>(
+pub fn run<UserApp: YanyaApp + 'static, T: Clone + 'static>(
+    graphics_creator: GraphicsCreator<T>,
     info: GraphicsInfo<T>,
     event_loop: EventLoop<()>,
     options: AppOptions,
     app_init: UserApp::AppInfo
 )
 {
-    let capabilities = info.physical_device
-        .surface_capabilities(&info.surface, Default::default())
-        .unwrap();
-
-    let composite_alpha =
-    {
-        let supported = capabilities.supported_composite_alpha;
-
-        let preferred = CompositeAlpha::Opaque;
-        let supports_preferred = supported.contains_enum(preferred);
-
-        if supports_preferred
-        {
-            preferred
-        } else
-        {
-            supported.into_iter().next().unwrap()
-        }
-    };
-
-    let formats = info.physical_device
-        .surface_formats(&info.surface, Default::default())
-        .unwrap();
-
-    let image_format = formats.iter().find(|(format, colorspace)|
-    {
-        format.numeric_format_color() == Some(NumericFormat::SRGB)
-            && *colorspace == ColorSpace::SrgbNonLinear
-    }).unwrap_or_else(|| &formats[0]).0;
-
     let device = info.device.clone();
-    let queue = info.queues[0].clone();
+    let queue = info.queues.graphics.clone();
+    let compute_queue = info.queues.compute.clone();
 
-    let render_info = RenderInfo::new(
-        info,
-        capabilities,
-        image_format,
-        composite_alpha
-    );
+    let render_info = RenderInfo::new(info, options.surface_format.as_ref());
 
     let mut handle_info: HandleEventInfo<UserApp, T> = HandleEventInfo::from(
         HandleEventInfoRaw{
@@ -598,7 +1298,9 @@ pub fn run<UserApp: YanyaApp + 'static, T: Clone>(
                 Default::default()
             ),
             queue,
+            compute_queue,
             render_info,
+            graphics_creator,
             device,
             options
         }
@@ -613,7 +1315,7 @@ pub fn run<UserApp: YanyaApp + 'static, T: Clone>(
     }).unwrap();
 }
 
-fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
+fn handle_event<UserApp: YanyaApp + 'static, T: Clone + 'static>(
     info: &mut HandleEventInfo<UserApp, T>,
     event: Event<()>,
     event_loop: &EventLoopWindowTarget<()>,
@@ -636,6 +1338,14 @@ fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
                     event_loop.exit()
                 },
                 WindowEvent::Resized(_) => info.window_resized = true,
+                WindowEvent::ScaleFactorChanged{..} =>
+                {
+                    // cached text was rasterized for the old dpi
+                    if let Some(engine) = info.engine.as_ref()
+                    {
+                        engine.clear_text_cache();
+                    }
+                },
                 WindowEvent::CursorMoved{position, ..} =>
                 {
                     if !info.initialized
@@ -643,14 +1353,18 @@ fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
                         return;
                     }
 
-                    let (width, height): (f64, f64) = info.render_info.surface_size().into();
-
-                    if width == 0.0 || height == 0.0
+                    let position = if let Some(x) = normalized_position(&info.render_info, position)
+                    {
+                        x
+                    } else
                     {
                         return;
-                    }
+                    };
 
-                    let position = ((position.x / width).clamp(0.0, 1.0), (position.y / height).clamp(0.0, 1.0));
+                    if let Some(engine) = info.engine.as_mut()
+                    {
+                        engine.set_mouse_position(Vector2::new(position.0 as f32, position.1 as f32));
+                    }
 
                     if let Some(app) = info.user_app.as_mut()
                     {
@@ -693,6 +1407,27 @@ fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
                         app.input(control);
                     }
                 },
+                WindowEvent::Touch(Touch{id, phase, location, ..}) =>
+                {
+                    if !info.initialized
+                    {
+                        return;
+                    }
+
+                    let position = if let Some(x) = normalized_position(&info.render_info, location)
+                    {
+                        x
+                    } else
+                    {
+                        return;
+                    };
+
+                    let control = Control::Touch{id, phase, position};
+                    if let Some(app) = info.user_app.as_mut()
+                    {
+                        app.input(control);
+                    }
+                },
                 WindowEvent::KeyboardInput{event, ..} =>
                 {
                     if !info.initialized
@@ -700,22 +1435,79 @@ fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
                         return;
                     }
 
+                    let text = event.text.as_ref().filter(|_|
+                    {
+                        event.state == ElementState::Pressed
+                    }).map(|text|
+                    {
+                        text.chars().filter(|c| !c.is_control()).collect::<String>()
+                    }).filter(|text| !text.is_empty());
+
                     let control = Control::Keyboard{
                         logical: event.logical_key,
                         keycode: event.physical_key,
-                        state: event.state
+                        state: event.state,
+                        repeat: event.repeat
                     };
 
                     if let Some(app) = info.user_app.as_mut()
+                    {
+                        app.input(control);
+
+                        if let Some(text) = text
+                        {
+                            app.input(Control::Text{text});
+                        }
+                    }
+                },
+                WindowEvent::Ime(ime) =>
+                {
+                    if !info.initialized
+                    {
+                        return;
+                    }
+
+                    let control = match ime
+                    {
+                        Ime::Preedit(text, cursor) => Control::ImePreedit{text, cursor},
+                        Ime::Commit(text) => Control::Text{text},
+                        Ime::Enabled | Ime::Disabled => return
+                    };
+
+                    if let Some(app) = info.user_app.as_mut()
+                    {
+                        app.input(control);
+                    }
+                },
+                _ => ()
+            }
+        },
+        Event::DeviceEvent{event: DeviceEvent::MouseMotion{delta}, ..} =>
+        {
+            if !info.initialized
+            {
+                return;
+            }
+
+            if let Some(app) = info.user_app.as_mut()
+            {
+                app.mouse_delta(delta);
+            }
+        },
+        Event::AboutToWait =>
+        {
+            #[cfg(feature = "gamepad")]
+            {
+                let user_app = &mut info.user_app;
+                info.gamepads.poll(|control|
+                {
+                    if let Some(app) = user_app.as_mut()
                     {
                         app.input(control);
                     }
-                },
-                _ => ()
+                });
             }
-        },
-        Event::AboutToWait =>
-        {
+
             let [x, y]: [u32; 2] = info.render_info.surface_size().into();
 
             if x == 0 || y == 0
@@ -724,12 +1516,42 @@ fn handle_event<UserApp: YanyaApp + 'static, T: Clone>(
             }
 
             handle_redraw(info, app_init);
+
+            if info.device_lost
+            {
+                eprintln!("device lost, recreating it");
+
+                if let Err(err) = recover_device(info)
+                {
+                    // theres nothing left to draw with
+                    eprintln!("couldnt recover from device loss ({err}), exiting");
+
+                    drop(info.user_app.take());
+
+                    event_loop.exit();
+                }
+            }
         },
         _ => ()
     }
 }
 
-fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
+fn normalized_position<T: Clone>(
+    render_info: &RenderInfo<T>,
+    position: PhysicalPosition<f64>
+) -> Option<(f64, f64)>
+{
+    let (width, height): (f64, f64) = render_info.surface_size().into();
+
+    if width == 0.0 || height == 0.0
+    {
+        return None;
+    }
+
+    Some(((position.x / width).clamp(0.0, 1.0), (position.y / height).clamp(0.0, 1.0)))
+}
+
+fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone + 'static>(
     info: &mut HandleEventInfo<UserApp, T>,
     app_init: &mut Option<UserApp::AppInfo>
 )
@@ -744,10 +1566,33 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
     {
         info.recreate_swapchain = false;
 
+        // transient failures (like out of date surfaces) get retried next frame
+        const MAX_FAILURES: u32 = 16;
+
         match info.render_info.recreate(info.window_resized)
         {
-            Ok(_) => (),
-            Err(e) => panic!("couldnt recreate swapchain ; -; ({e})")
+            Ok(_) => info.recreate_failures = 0,
+            Err(Validated::Error(VulkanError::DeviceLost)) =>
+            {
+                info.device_lost = true;
+
+                return;
+            },
+            Err(e) =>
+            {
+                info.recreate_failures += 1;
+
+                if info.recreate_failures >= MAX_FAILURES
+                {
+                    panic!("couldnt recreate swapchain ; -; ({e})");
+                }
+
+                eprintln!("couldnt recreate swapchain, retrying ({e})");
+
+                info.recreate_swapchain = true;
+
+                return;
+            }
         }
 
         if !info.initialized
@@ -759,6 +1604,9 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
         info.engine.as_mut().unwrap().swap_pipelines(&resource_uploader);
         info.user_app.as_mut().unwrap().swap_pipelines(&resource_uploader);
 
+        let extent = info.render_info.swapchain().image_extent();
+        info.user_app.as_mut().unwrap().framebuffer_resized(extent);
+
         if info.window_resized
         {
             info.user_app.as_mut().unwrap().resize(info.render_info.aspect());
@@ -768,13 +1616,23 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
     }
 
     builder.set_scissor(0, vec![Scissor::default()].into()).unwrap();
+    builder.set_viewport(0, [info.render_info.viewport.clone()].into_iter().collect()).unwrap();
+    builder.set_depth_bias(0.0, 0.0, 0.0).unwrap();
 
     let acquired =
-        match swapchain::acquire_next_image(info.render_info.swapchain.clone(), None)
+        match swapchain::acquire_next_image(info.render_info.swapchain().clone(), None)
         {
             Ok(x) => Some(x),
-            Err(Validated::Error(VulkanError::OutOfDate)) =>
+            Err(Validated::Error(VulkanError::OutOfDate | VulkanError::SurfaceLost)) =>
+            {
+                info.recreate_swapchain = true;
+
+                None
+            },
+            Err(Validated::Error(VulkanError::DeviceLost)) =>
             {
+                info.device_lost = true;
+
                 None
             },
             Err(e) =>
@@ -796,10 +1654,13 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
             info.initialized = true;
 
             info.engine = Some(Engine::new(
-                &info.options.assets_paths,
+                &mut info.options,
                 info.render_info.resource_uploader(&mut builder),
                 info.device.clone(),
-                info.options.shaders_query.take().unwrap()
+                create_compute_queue(&info.queue, info.compute_queue.clone()),
+                info.render_info.offscreen_creator(),
+                info.render_info.capture_error(),
+                info.render_info.window()
             ));
 
             info.user_app = {
@@ -808,7 +1669,8 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
                     .unwrap()
                     .init_partial_info(
                         info.render_info.resource_uploader(&mut builder),
-                        info.render_info.size()
+                        info.render_info.size(),
+                        info.render_info.frame_info(info.frame_index, info.start_time.elapsed().as_secs_f64())
                     );
 
                 let app_init = app_init.take().unwrap();
@@ -826,12 +1688,19 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
             image_index: image_index as usize,
             render_info: &mut info.render_info,
             previous_time: &mut info.previous_time,
-            frame_parity: info.frame_parity
+            fixed_accumulator: &mut info.fixed_accumulator,
+            fixed_timestep: info.options.fixed_timestep,
+            max_delta: info.options.max_delta,
+            frame_index: info.frame_index,
+            elapsed: info.start_time.elapsed().as_secs_f64(),
+            frame_parity: info.frame_parity,
+            subpass_contents: info.options.subpass_contents
         };
 
+        info.frame_index += 1;
         info.frame_parity = !info.frame_parity;
 
-        let command_buffer = run_frame(
+        let (command_buffer, capture) = run_frame(
             run_frame_info,
             info.user_app.as_mut().unwrap()
         );
@@ -841,27 +1710,393 @@ fn handle_redraw<UserApp: YanyaApp + 'static, T: Clone>(
             fence.cleanup_finished();
         }
 
+        let mut compute_waits = info.engine.as_ref().unwrap().compute().take_pending();
+        match info.render_info.flush_transfers()
+        {
+            Ok(transfers) => compute_waits.extend(transfers),
+            Err(DeviceLost) =>
+            {
+                mem::forget(compute_waits);
+
+                info.device_lost = true;
+
+                return;
+            }
+        }
+
         info.recreate_swapchain |= suboptimal;
-        info.recreate_swapchain |= execute_builder(
+
+        let executed = execute_builder(
             info.queue.clone(),
-            info.render_info.swapchain.clone(),
+            info.render_info.swapchain().clone(),
             &mut info.fence,
             FrameData{
                 command_buffer,
                 acquire_future,
+                compute_waits,
                 image_index
             }
         );
+
+        match executed
+        {
+            Ok(recreate) => info.recreate_swapchain |= recreate,
+            Err(DeviceLost) =>
+            {
+                info.device_lost = true;
+
+                return;
+            }
+        }
+
+        // theres no fence when the swapchain went out of date and nothing got presented
+        if let Some(buffer) = capture.filter(|_| info.fence.is_some())
+        {
+            if let Err(DeviceLost) = wait_future(&info.queue, info.fence.take().unwrap())
+            {
+                info.device_lost = true;
+
+                return;
+            }
+
+            let image = captured_to_rgba(buffer, &info.render_info.images[image_index as usize]);
+            info.user_app.as_mut().unwrap().frame_captured(image);
+        }
+    }
+}
+
+// everything made on the lost device gets made again on a new one, the app keeps
+// its state and recreates its own gpu objects in YanyaApp::on_device_lost
+fn recover_device<UserApp: YanyaApp, T: Clone + 'static>(
+    info: &mut HandleEventInfo<UserApp, T>
+) -> Result<(), String>
+{
+    mem::forget(info.fence.take());
+
+    // the leaked futures keep the old swapchain alive so the window needs a new surface
+    let window = info.render_info.window().unwrap();
+    let surface = Surface::from_window(info.device.instance().clone(), window)
+        .map_err(|err| err.to_string())?;
+
+    let graphics_info = (info.graphics_creator)(Some(surface)).map_err(|err| err.to_string())?;
+
+    info.device = graphics_info.device.clone();
+    info.queue = graphics_info.queues.graphics.clone();
+    info.compute_queue = graphics_info.queues.compute.clone();
+    info.command_allocator = StandardCommandBufferAllocator::new(info.device.clone(), Default::default());
+
+    // keep the clear color the app set
+    let clear_color = info.render_info.clear_values[0];
+
+    info.render_info = RenderInfo::new(graphics_info, info.options.surface_format.as_ref());
+    info.render_info.clear_values[0] = clear_color;
+
+    info.recreate_swapchain = false;
+    info.recreate_failures = 0;
+
+    if let Some(engine) = info.engine.as_mut()
+    {
+        let frame_info = info.render_info.frame_info(
+            info.frame_index,
+            info.start_time.elapsed().as_secs_f64()
+        );
+
+        recreate_engine(
+            &info.render_info,
+            &info.command_allocator,
+            &info.queue,
+            info.compute_queue.clone(),
+            engine,
+            info.user_app.as_mut(),
+            frame_info
+        ).map_err(|_| "lost the new device too".to_owned())?;
+    }
+
+    info.device_lost = false;
+
+    Ok(())
+}
+
+// returns after the app recreated its objects and all of their uploads finished
+fn recreate_engine<UserApp: YanyaApp, T: Clone + 'static>(
+    render_info: &RenderInfo<T>,
+    command_allocator: &StandardCommandBufferAllocator,
+    queue: &Arc<Queue>,
+    compute_queue: Option<Arc<Queue>>,
+    engine: &mut Engine,
+    user_app: Option<&mut UserApp>,
+    frame_info: FrameInfo
+) -> Result<(), DeviceLost>
+{
+    let mut builder = frame_builder(command_allocator, queue, &render_info.viewport);
+
+    // the compute work that was waiting belongs to the lost device
+    mem::forget(engine.compute().take_pending());
+
+    engine.recreate(
+        render_info.resource_uploader(&mut builder),
+        render_info.device.clone(),
+        create_compute_queue(queue, compute_queue),
+        render_info.offscreen_creator(),
+        render_info.capture_error()
+    );
+
+    if let Some(user_app) = user_app
+    {
+        let init_info = engine.init_partial_info(
+            render_info.resource_uploader(&mut builder),
+            render_info.size(),
+            frame_info
+        );
+
+        user_app.on_device_lost(init_info);
+    }
+
+    submit_and_wait(render_info, queue, builder.build().unwrap(), Vec::new())
+}
+
+fn create_compute_queue(queue: &Arc<Queue>, compute_queue: Option<Arc<Queue>>) -> ComputeQueue
+{
+    let graphics_family = queue.queue_family_index();
+
+    ComputeQueue::new(compute_queue.unwrap_or_else(|| queue.clone()), graphics_family)
+}
+
+fn frame_builder(
+    command_allocator: &StandardCommandBufferAllocator,
+    queue: &Arc<Queue>,
+    viewport: &Viewport
+) -> CommandBuilderType
+{
+    let mut builder = AutoCommandBufferBuilder::primary(
+        command_allocator,
+        queue.queue_family_index(),
+        CommandBufferUsage::OneTimeSubmit
+    ).unwrap();
+
+    builder.set_scissor(0, vec![Scissor::default()].into()).unwrap();
+    builder.set_viewport(0, [viewport.clone()].into_iter().collect()).unwrap();
+    builder.set_depth_bias(0.0, 0.0, 0.0).unwrap();
+
+    builder
+}
+
+// renders the requested amount of frames into an offscreen image without a window,
+// every frame gets read back after its done
+pub fn run_headless<UserApp: YanyaApp + 'static, T: Clone + 'static>(
+    graphics_creator: GraphicsCreator<T>,
+    info: GraphicsInfo<T>,
+    mut options: AppOptions,
+    app_init: UserApp::AppInfo,
+    extent: [u32; 2],
+    frames: usize
+) -> Result<Vec<RgbaImage>, AppError>
+{
+    let mut queue = info.queues.graphics.clone();
+    let compute_queue = info.queues.compute.clone();
+
+    let mut render_info = RenderInfo::new_headless(info, extent);
+
+    let mut command_allocator = StandardCommandBufferAllocator::new(
+        render_info.device.clone(),
+        Default::default()
+    );
+
+    let mut builder = frame_builder(&command_allocator, &queue, &render_info.viewport);
+
+    let start_time = Instant::now();
+
+    let mut engine = Engine::new(
+        &mut options,
+        render_info.resource_uploader(&mut builder),
+        render_info.device.clone(),
+        create_compute_queue(&queue, compute_queue),
+        render_info.offscreen_creator(),
+        render_info.capture_error(),
+        None
+    );
+
+    let mut user_app = {
+        let init_info = engine.init_partial_info(
+            render_info.resource_uploader(&mut builder),
+            render_info.size(),
+            render_info.frame_info(0, 0.0)
+        );
+
+        UserApp::init(init_info, app_init)
+    };
+
+    let mut init_builder = Some(builder);
+
+    let mut previous_time = Instant::now();
+    let mut fixed_accumulator = 0.0;
+
+    let mut captured = Vec::with_capacity(frames);
+    while captured.len() < frames
+    {
+        let frame_index = captured.len();
+
+        // the first frame also records the init uploads
+        let builder = init_builder.take().unwrap_or_else(||
+        {
+            frame_builder(&command_allocator, &queue, &render_info.viewport)
+        });
+
+        engine.request_capture();
+
+        let run_frame_info = RunFrameInfo
+        {
+            engine: &mut engine,
+            builder,
+            image_index: 0,
+            render_info: &mut render_info,
+            previous_time: &mut previous_time,
+            fixed_accumulator: &mut fixed_accumulator,
+            fixed_timestep: options.fixed_timestep,
+            max_delta: options.max_delta,
+            frame_index: frame_index as u64,
+            elapsed: start_time.elapsed().as_secs_f64(),
+            frame_parity: frame_index % 2 == 1,
+            subpass_contents: options.subpass_contents
+        };
+
+        let (command_buffer, capture) = run_frame(run_frame_info, &mut user_app);
+
+        let compute_waits = engine.compute().take_pending();
+
+        match submit_and_wait(&render_info, &queue, command_buffer, compute_waits)
+        {
+            Ok(()) => captured.push(captured_to_rgba(capture.unwrap(), &render_info.images[0])),
+            Err(DeviceLost) =>
+            {
+                // the same frame gets rendered again on the new device
+                eprintln!("device lost, recreating it");
+
+                let info = graphics_creator(None)?;
+
+                queue = info.queues.graphics.clone();
+                let compute_queue = info.queues.compute.clone();
+
+                command_allocator = StandardCommandBufferAllocator::new(
+                    info.device.clone(),
+                    Default::default()
+                );
+
+                render_info = RenderInfo::new_headless(info, extent);
+
+                let frame_info = render_info.frame_info(
+                    frame_index as u64,
+                    start_time.elapsed().as_secs_f64()
+                );
+
+                recreate_engine(
+                    &render_info,
+                    &command_allocator,
+                    &queue,
+                    compute_queue,
+                    &mut engine,
+                    Some(&mut user_app),
+                    frame_info
+                ).map_err(|_| AppError::DeviceLost)?;
+            }
+        }
+    }
+
+    Ok(captured)
+}
+
+// dropping a future that isnt finished waits on it and unwraps the result, which panics
+// once the device is lost, so the queue gets waited on first and lost futures are leaked
+fn wait_future(queue: &Arc<Queue>, future: impl GpuFuture) -> Result<(), DeviceLost>
+{
+    let result = future.flush().and_then(|_|
+    {
+        queue.with(|mut queue| queue.wait_idle()).map_err(Validated::Error)
+    });
+
+    match result
+    {
+        Ok(()) => Ok(()),
+        Err(Validated::Error(VulkanError::DeviceLost)) =>
+        {
+            mem::forget(future);
+
+            Err(DeviceLost)
+        },
+        Err(e) =>
+        {
+            let e = match e
+            {
+                Validated::Error(x) => format!("{x}"),
+                Validated::ValidationError(x) => format!("error validating {x}")
+            };
+
+            panic!("error waiting for the gpu: {e}")
+        }
+    }
+}
+
+fn submit_and_wait<T: Clone>(
+    render_info: &RenderInfo<T>,
+    queue: &Arc<Queue>,
+    command_buffer: Arc<PrimaryAutoCommandBuffer>,
+    mut waits: Vec<Box<dyn GpuFuture>>
+) -> Result<(), DeviceLost>
+{
+    match render_info.flush_transfers()
+    {
+        Ok(transfers) => waits.extend(transfers),
+        Err(DeviceLost) =>
+        {
+            mem::forget(waits);
+
+            return Err(DeviceLost);
+        }
     }
+
+    let future = waits.into_iter().fold(
+        Box::new(sync::now(render_info.device.clone())) as Box<dyn GpuFuture>,
+        |acc, future| Box::new(acc.join(future))
+    ).then_execute(queue.clone(), command_buffer)
+        .unwrap()
+        .then_signal_fence();
+
+    wait_future(queue, future)
 }
 
-type FutureInner = PresentFuture<CommandBufferExecFuture<SwapchainAcquireFuture>>;
+type FutureInner = PresentFuture<CommandBufferExecFuture<Box<dyn GpuFuture>>>;
 type FutureType = Option<Arc<FenceSignalFuture<FutureInner>>>;
 
+pub(crate) struct DeviceLost;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError
+{
+    // the surface doesnt allow copying out of its images
+    UnsupportedUsage,
+    // only 8 bit rgba and bgra formats can be captured
+    UnsupportedFormat(Format)
+}
+
+impl Display for CaptureError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::UnsupportedUsage => write!(f, "the surface doesnt support copying from its images"),
+            Self::UnsupportedFormat(format) => write!(f, "frames in {format:?} format cant be captured")
+        }
+    }
+}
+
+impl error::Error for CaptureError {}
+
 struct FrameData
 {
     command_buffer: Arc<PrimaryAutoCommandBuffer>,
     acquire_future: SwapchainAcquireFuture,
+    compute_waits: Vec<Box<dyn GpuFuture>>,
     image_index: u32
 }
 
@@ -872,28 +2107,142 @@ struct RunFrameInfo<'a, T>
     builder: CommandBuilderType,
     render_info: &'a mut RenderInfo<T>,
     previous_time: &'a mut Instant,
-    frame_parity: bool
+    fixed_accumulator: &'a mut f32,
+    fixed_timestep: Option<f32>,
+    max_delta: Option<f32>,
+    frame_index: u64,
+    elapsed: f64,
+    frame_parity: bool,
+    subpass_contents: SubpassContents
+}
+
+fn capture_image<T>(
+    render_info: &RenderInfo<T>,
+    builder: &mut CommandBuilderType,
+    image_index: usize
+) -> Subbuffer<[u8]>
+{
+    let image = render_info.images[image_index].clone();
+    let [width, height, _] = image.extent();
+
+    let buffer = Buffer::new_slice(
+        render_info.memory_allocator.clone(),
+        BufferCreateInfo{
+            usage: BufferUsage::TRANSFER_DST,
+            ..Default::default()
+        },
+        AllocationCreateInfo{
+            memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+            ..Default::default()
+        },
+        width as u64 * height as u64 * 4
+    ).unwrap();
+
+    builder.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(image, buffer.clone()))
+        .unwrap();
+
+    buffer
+}
+
+// the data is already srgb encoded, only the channel order might be different
+fn captured_to_rgba(buffer: Subbuffer<[u8]>, image: &Image) -> RgbaImage
+{
+    let [width, height, _] = image.extent();
+
+    let mut data = buffer.read().unwrap().to_vec();
+
+    let is_bgra = matches!(image.format(), Format::B8G8R8A8_SRGB | Format::B8G8R8A8_UNORM);
+
+    if is_bgra
+    {
+        data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+    }
+
+    RgbaImage::new(data, width, height)
 }
 
 fn run_frame<UserApp: YanyaApp, T: Clone>(
     mut frame_info: RunFrameInfo<T>,
     user_app: &mut UserApp
-) -> Arc<PrimaryAutoCommandBuffer>
+) -> (Arc<PrimaryAutoCommandBuffer>, Option<Subbuffer<[u8]>>)
 {
     let delta_time = frame_info.previous_time.elapsed().as_secs_f32();
     *frame_info.previous_time = Instant::now();
 
+    frame_info.engine.push_frame_time(delta_time);
+    frame_info.engine.reset_transient();
+
+    let delta_time = frame_info.max_delta.map(|max| delta_time.min(max)).unwrap_or(delta_time);
+
+    frame_info.engine.poll_assets(
+        &mut frame_info.render_info.resource_uploader(&mut frame_info.builder)
+    );
+
+    let interpolation = if let Some(step) = frame_info.fixed_timestep
+    {
+        // dont try to catch up forever after a long stall
+        const MAX_STEPS: u32 = 8;
+
+        let accumulator = &mut *frame_info.fixed_accumulator;
+        *accumulator += delta_time;
+
+        let mut steps = 0;
+        while *accumulator >= step
+        {
+            if steps == MAX_STEPS
+            {
+                *accumulator %= step;
+                break;
+            }
+
+            user_app.fixed_update(step);
+
+            *accumulator -= step;
+            steps += 1;
+        }
+
+        *accumulator / step
+    } else
+    {
+        1.0
+    };
+
     {
         let object_create_info = frame_info.engine
             .object_create_partial_info(
                 frame_info.render_info.resource_uploader(&mut frame_info.builder),
                 frame_info.render_info.size(),
+                frame_info.render_info.frame_info(frame_info.frame_index, frame_info.elapsed),
                 frame_info.frame_parity
             );
 
         user_app.update(object_create_info, delta_time);
     }
 
+    {
+        let object_create_info = frame_info.engine
+            .object_create_partial_info(
+                frame_info.render_info.resource_uploader(&mut frame_info.builder),
+                frame_info.render_info.size(),
+                frame_info.render_info.frame_info(frame_info.frame_index, frame_info.elapsed),
+                frame_info.frame_parity
+            );
+
+        let draw_info = DrawInfo::new(
+            object_create_info,
+            &frame_info.render_info.pipelines
+        ).with_interpolation(interpolation);
+
+        user_app.draw_offscreen(draw_info);
+    }
+
+    // the first attachment is always the color one
+    if let Some(color) = frame_info.engine.take_clear_color()
+    {
+        frame_info.render_info.clear_values[0] = Some(ClearValue::Float(color));
+    }
+
     frame_info.builder
         .begin_render_pass(
             RenderPassBeginInfo{
@@ -903,7 +2252,7 @@ fn run_frame<UserApp: YanyaApp, T: Clone>(
                 )
             },
             SubpassBeginInfo{
-                contents: SubpassContents::Inline,
+                contents: frame_info.subpass_contents,
                 ..Default::default()
             }
         )
@@ -914,19 +2263,30 @@ fn run_frame<UserApp: YanyaApp, T: Clone>(
             .object_create_partial_info(
                 frame_info.render_info.resource_uploader(&mut frame_info.builder),
                 frame_info.render_info.size(),
+                frame_info.render_info.frame_info(frame_info.frame_index, frame_info.elapsed),
                 frame_info.frame_parity
             );
 
         let draw_info = DrawInfo::new(
             object_create_info,
             &frame_info.render_info.pipelines
-        );
+        ).with_interpolation(interpolation)
+            .with_framebuffer(
+                frame_info.render_info.framebuffers[frame_info.image_index].clone(),
+                frame_info.subpass_contents
+            );
 
         user_app.draw(draw_info);
     }
 
     frame_info.builder.end_render_pass(Default::default()).unwrap();
-    frame_info.builder.build().unwrap()
+
+    let capture = frame_info.engine.take_capture_request().then(||
+    {
+        capture_image(frame_info.render_info, &mut frame_info.builder, frame_info.image_index)
+    });
+
+    (frame_info.builder.build().unwrap(), capture)
 }
 
 fn execute_builder(
@@ -934,34 +2294,54 @@ fn execute_builder(
     swapchain: Arc<Swapchain>,
     fence: &mut FutureType,
     frame_data: FrameData
-) -> bool
+) -> Result<bool, DeviceLost>
 {
     let FrameData{
         command_buffer,
         acquire_future,
+        compute_waits,
         image_index
     } = frame_data;
 
-    fence.take();
+    if let Some(previous) = fence.take()
+    {
+        if let Err(DeviceLost) = wait_future(&queue, previous)
+        {
+            mem::forget((acquire_future, compute_waits));
+
+            return Err(DeviceLost);
+        }
+    }
+
+    let wait_future = compute_waits.into_iter().fold(
+        Box::new(acquire_future) as Box<dyn GpuFuture>,
+        |acc, future| Box::new(acc.join(future))
+    );
 
-    let current_fence = acquire_future
+    let current_fence = wait_future
         .then_execute(queue.clone(), command_buffer)
         .unwrap()
         .then_swapchain_present(
             queue,
             SwapchainPresentInfo::swapchain_image_index(swapchain, image_index)
-        ).then_signal_fence_and_flush();
+        ).then_signal_fence();
 
     let mut recreate_swapchain = false;
-    *fence = match current_fence
+    *fence = match current_fence.flush()
     {
         #[allow(clippy::arc_with_non_send_sync)]
-        Ok(fence) => Some(Arc::new(fence)),
+        Ok(()) => Some(Arc::new(current_fence)),
         Err(Validated::Error(VulkanError::OutOfDate)) =>
         {
             recreate_swapchain = true;
             None
         },
+        Err(Validated::Error(VulkanError::DeviceLost)) =>
+        {
+            mem::forget(current_fence);
+
+            return Err(DeviceLost);
+        },
         Err(e) =>
         {
             let e = match e
@@ -974,5 +2354,5 @@ fn execute_builder(
         }
     };
 
-    recreate_swapchain
+    Ok(recreate_swapchain)
 }